@@ -0,0 +1,235 @@
+//! Output backends for the audio engine. Besides a real device through cpal, the
+//! engine can render on a timer with nowhere to send the audio, or stream it to a
+//! WAV file, so it keeps working without a sound card.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Environment variable that overrides the configured backend: `device`, `null` or `file:<path>`
+pub const BACKEND_ENV_VAR: &str = "BEATR_AUDIO_BACKEND";
+
+/// Where the engine sends the audio it renders
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum AudioBackend {
+    /// A sound card, chosen by the preferred device setting
+    #[default]
+    Device,
+    /// No output; the engine runs on a timer as if a device were playing
+    Null,
+    /// Write everything the engine plays to a WAV file
+    File(PathBuf),
+}
+
+impl AudioBackend {
+    /// Backend requested through `BEATR_AUDIO_BACKEND`, if set and recognised
+    pub fn from_env() -> Option<Self> {
+        Self::parse(&std::env::var(BACKEND_ENV_VAR).ok()?)
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "device" => Some(AudioBackend::Device),
+            "null" => Some(AudioBackend::Null),
+            other => other
+                .strip_prefix("file:")
+                .filter(|path| !path.is_empty())
+                .map(|path| AudioBackend::File(PathBuf::from(path))),
+        }
+    }
+
+    /// Name shown in place of a device name
+    pub fn display_name(&self) -> String {
+        match self {
+            AudioBackend::Device => "Audio Device".to_string(),
+            AudioBackend::Null => "No Output".to_string(),
+            AudioBackend::File(path) => format!("WAV File ({})", path.display()),
+        }
+    }
+}
+
+/// Renders one buffer of mono audio
+pub type RenderFn = Box<dyn FnMut(&mut [f32]) + Send>;
+
+/// Runs a render function on its own thread at the pace a sound card would pull
+/// buffers, optionally writing the output to a WAV file
+pub struct TimerSink {
+    playing: Arc<AtomicBool>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl TimerSink {
+    /// Render and discard the audio
+    pub fn null(render: RenderFn, sample_rate: u32, buffer_size: u32) -> Result<Self> {
+        Self::spawn(render, sample_rate, buffer_size, None)
+    }
+
+    /// Render into a 32-bit float mono WAV file at `path`, replacing any existing file
+    pub fn file(render: RenderFn, sample_rate: u32, buffer_size: u32, path: &Path) -> Result<Self> {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        let writer = hound::WavWriter::create(path, spec)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        Self::spawn(render, sample_rate, buffer_size, Some(writer))
+    }
+
+    fn spawn(
+        mut render: RenderFn,
+        sample_rate: u32,
+        buffer_size: u32,
+        mut writer: Option<hound::WavWriter<BufWriter<File>>>,
+    ) -> Result<Self> {
+        // Like a stream, the sink starts paused until `play`
+        let playing = Arc::new(AtomicBool::new(false));
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_playing = Arc::clone(&playing);
+        let thread_stop = Arc::clone(&stop);
+
+        let buffer_duration = Duration::from_secs_f64(buffer_size as f64 / sample_rate as f64);
+        let thread = std::thread::Builder::new()
+            .name("audio-timer".to_string())
+            .spawn(move || {
+                let mut buffer = vec![0.0f32; buffer_size as usize];
+                let mut unflushed = 0;
+                let mut next_buffer = Instant::now();
+
+                while !thread_stop.load(Ordering::Relaxed) {
+                    if thread_playing.load(Ordering::Relaxed) {
+                        render(&mut buffer);
+
+                        if let Some(wav) = writer.as_mut() {
+                            let written = buffer.iter().try_for_each(|s| wav.write_sample(*s));
+                            if let Err(e) = written {
+                                eprintln!("Audio file error, recording stopped: {}", e);
+                                writer = None;
+                            } else {
+                                // Keep the header current so the file is playable while recording
+                                unflushed += buffer.len();
+                                if unflushed >= sample_rate as usize {
+                                    let _ = wav.flush();
+                                    unflushed = 0;
+                                }
+                            }
+                        }
+                    }
+
+                    // Schedule against the clock rather than sleeping a fixed time, so
+                    // rendering time does not add up; after a stall, don't try to catch up
+                    next_buffer += buffer_duration;
+                    let now = Instant::now();
+                    if next_buffer > now {
+                        std::thread::sleep(next_buffer - now);
+                    } else {
+                        next_buffer = now;
+                    }
+                }
+
+                if let Some(wav) = writer {
+                    if let Err(e) = wav.finalize() {
+                        eprintln!("Failed to finish audio file: {}", e);
+                    }
+                }
+            })?;
+
+        Ok(TimerSink {
+            playing,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn play(&self) {
+        self.playing.store(true, Ordering::Relaxed);
+    }
+
+    pub fn pause(&self) {
+        self.playing.store(false, Ordering::Relaxed);
+    }
+
+    /// Stop the thread and finish the WAV file, if any
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for TimerSink {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_backend_from_env_value() {
+        assert_eq!(AudioBackend::parse("null"), Some(AudioBackend::Null));
+        assert_eq!(AudioBackend::parse("device"), Some(AudioBackend::Device));
+        assert_eq!(
+            AudioBackend::parse("file:/tmp/out.wav"),
+            Some(AudioBackend::File(PathBuf::from("/tmp/out.wav")))
+        );
+        assert_eq!(AudioBackend::parse("file:"), None);
+        assert_eq!(AudioBackend::parse("speakers"), None);
+    }
+
+    #[test]
+    fn test_null_sink_runs_render_on_timer() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let render_calls = Arc::clone(&calls);
+        let render: RenderFn = Box::new(move |data| {
+            assert_eq!(data.len(), 64);
+            render_calls.fetch_add(1, Ordering::Relaxed);
+        });
+
+        let mut sink = TimerSink::null(render, 44100, 64).unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(calls.load(Ordering::Relaxed), 0, "Sink should start paused");
+
+        sink.play();
+        std::thread::sleep(Duration::from_millis(100));
+        sink.stop();
+        assert!(calls.load(Ordering::Relaxed) > 0);
+
+        println!("✅ Null sink test passed");
+    }
+
+    #[test]
+    fn test_file_sink_writes_wav() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("capture.wav");
+        let render: RenderFn = Box::new(|data| data.fill(0.25));
+
+        let mut sink = TimerSink::file(render, 8000, 256, &path).unwrap();
+        sink.play();
+        std::thread::sleep(Duration::from_millis(100));
+        sink.stop();
+
+        let mut reader = hound::WavReader::open(&path).unwrap();
+        assert_eq!(reader.spec().sample_rate, 8000);
+        assert_eq!(reader.spec().channels, 1);
+        let samples: Vec<f32> = reader.samples::<f32>().map(|s| s.unwrap()).collect();
+        assert!(!samples.is_empty());
+        assert_eq!(samples.len() % 256, 0, "Whole buffers are written");
+        assert!(samples.iter().all(|s| *s == 0.25));
+
+        println!("✅ File sink test passed");
+    }
+}
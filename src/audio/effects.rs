@@ -0,0 +1,588 @@
+//! Master bus effects applied to everything the engine plays, after the master
+//! volume: EQ, compressor, reverb and a brickwall limiter, in that order.
+
+use fundsp::prelude::{
+    amp_db, bell, db_amp, dc, highshelf, limiter, lowshelf, pass, reverb_stereo, shared, var,
+    AudioUnit, Shared,
+};
+use serde::{Deserialize, Serialize};
+
+/// Three-band EQ: low shelf, mid bell and high shelf
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EqSettings {
+    pub enabled: bool,
+    pub low_freq: f32, // Hz
+    pub low_gain_db: f32,
+    pub mid_freq: f32, // Hz
+    pub mid_q: f32,
+    pub mid_gain_db: f32,
+    pub high_freq: f32, // Hz
+    pub high_gain_db: f32,
+}
+
+impl Default for EqSettings {
+    fn default() -> Self {
+        EqSettings {
+            enabled: false,
+            low_freq: 100.0,
+            low_gain_db: 0.0,
+            mid_freq: 1000.0,
+            mid_q: 0.7,
+            mid_gain_db: 0.0,
+            high_freq: 8000.0,
+            high_gain_db: 0.0,
+        }
+    }
+}
+
+/// Feed-forward peak compressor
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompressorSettings {
+    pub enabled: bool,
+    pub threshold_db: f32,
+    pub ratio: f32, // Input dB over the threshold per output dB, at least 1
+    pub attack_ms: f32,
+    pub release_ms: f32,
+    pub makeup_db: f32,
+}
+
+impl Default for CompressorSettings {
+    fn default() -> Self {
+        CompressorSettings {
+            enabled: false,
+            threshold_db: -18.0,
+            ratio: 4.0,
+            attack_ms: 10.0,
+            release_ms: 100.0,
+            makeup_db: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReverbSettings {
+    pub enabled: bool,
+    pub room_size: f32, // Meters
+    pub time: f32,      // Seconds to decay by 60 dB
+    pub damping: f32,   // High frequency damping, 0 to 1
+    pub mix: f32,       // Wet share of the output, 0 to 1
+}
+
+impl Default for ReverbSettings {
+    fn default() -> Self {
+        ReverbSettings {
+            enabled: false,
+            room_size: 15.0,
+            time: 1.5,
+            damping: 0.5,
+            mix: 0.2,
+        }
+    }
+}
+
+impl ReverbSettings {
+    fn room(&self) -> ReverbRoom {
+        ReverbRoom {
+            room_size: self.room_size,
+            time: self.time,
+            damping: self.damping,
+        }
+    }
+}
+
+/// The settings a reverb is built from. A change to any other setting, like the mix,
+/// doesn't need a new reverb. Shared with the mixer's reverb bus.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct ReverbRoom {
+    pub room_size: f32, // Meters
+    pub time: f32,      // Seconds to decay by 60 dB
+    pub damping: f32,   // High frequency damping, 0 to 1
+}
+
+/// Look-ahead limiter followed by a hard clip, so nothing ever exceeds the ceiling
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LimiterSettings {
+    pub enabled: bool,
+    pub ceiling_db: f32,
+}
+
+impl Default for LimiterSettings {
+    fn default() -> Self {
+        LimiterSettings {
+            enabled: false,
+            ceiling_db: -0.3,
+        }
+    }
+}
+
+/// Master effect parameters, saved with the project. Everything starts disabled,
+/// which leaves the audio untouched.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MasterEffects {
+    pub eq: EqSettings,
+    pub compressor: CompressorSettings,
+    pub reverb: ReverbSettings,
+    pub limiter: LimiterSettings,
+}
+
+impl MasterEffects {
+    /// True when no effect is enabled
+    pub fn is_bypassed(&self) -> bool {
+        !(self.eq.enabled || self.compressor.enabled || self.reverb.enabled || self.limiter.enabled)
+    }
+}
+
+// Q of the shelving filters
+const SHELF_Q: f32 = 0.707;
+// Limiter attack in seconds, which is also how far it looks ahead (and delays the audio)
+const LIMITER_ATTACK: f32 = 0.002;
+const LIMITER_RELEASE: f32 = 0.1;
+
+/// The master effects for one output. Changes arrive as an `EffectsUpdate` with the
+/// reverb already built, so switching parameters never allocates and tails and filter
+/// state carry on.
+pub struct EffectsChain {
+    settings: MasterEffects,
+    sample_rate: f32,
+    eq: Equalizer,
+    compressor: Compressor,
+    reverb: Option<Box<dyn AudioUnit>>,
+    limiter: Box<dyn AudioUnit>,
+}
+
+impl EffectsChain {
+    /// Chain starting from `update`, prepared against an empty plan
+    pub fn prepared(mut update: EffectsUpdate, sample_rate: f32) -> Self {
+        let mut chain = EffectsChain {
+            settings: MasterEffects::default(),
+            sample_rate,
+            eq: Equalizer::new(sample_rate),
+            compressor: Compressor::new(),
+            reverb: None,
+            limiter: new_limiter(sample_rate),
+        };
+        chain.apply(&mut update);
+        chain
+    }
+
+    /// Switch to a prepared change without allocating. `update` is left holding the
+    /// reverb it replaced, to be freed away from the audio thread.
+    pub fn apply(&mut self, update: &mut EffectsUpdate) {
+        self.settings = update.settings;
+        if update.reverb.is_some() {
+            std::mem::swap(&mut self.reverb, &mut update.reverb);
+        }
+
+        self.eq.set(&self.settings.eq, self.sample_rate);
+        self.compressor
+            .configure(&self.settings.compressor, self.sample_rate);
+    }
+
+    /// Clear filter memory, envelopes and the reverb tail, as if the chain was just built
+    pub fn reset(&mut self) {
+        self.eq.unit.reset();
+        self.compressor.envelope = 0.0;
+        if let Some(reverb) = self.reverb.as_mut() {
+            reverb.reset();
+        }
+        self.limiter.reset();
+    }
+
+    /// Run `buffer` through the enabled effects in place
+    pub fn process(&mut self, buffer: &mut [f32]) {
+        let settings = self.settings;
+        if settings.is_bypassed() {
+            return;
+        }
+
+        let mix = settings.reverb.mix.clamp(0.0, 1.0);
+        let ceiling = db_amp(settings.limiter.ceiling_db.min(0.0));
+
+        for sample in buffer.iter_mut() {
+            let mut x = *sample;
+
+            if settings.eq.enabled {
+                x = self.eq.unit.filter_mono(x);
+            }
+
+            if settings.compressor.enabled {
+                x = self.compressor.process(x);
+            }
+
+            if settings.reverb.enabled {
+                if let Some(reverb) = self.reverb.as_mut() {
+                    let (left, right) = reverb.filter_stereo(x, x);
+                    x = x * (1.0 - mix) + (left + right) * 0.5 * mix;
+                }
+            }
+
+            if settings.limiter.enabled {
+                // The limiter holds its input within -1...1, so scale around it
+                x = (self.limiter.filter_mono(x / ceiling) * ceiling).clamp(-ceiling, ceiling);
+            }
+
+            *sample = x;
+        }
+    }
+}
+
+/// The master effects last handed to a chain, and the room its reverb was built for.
+/// Lives away from the audio thread, where it works out what each change has to build.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EffectsPlan {
+    settings: MasterEffects,
+    reverb_room: Option<ReverbRoom>,
+}
+
+impl EffectsPlan {
+    pub fn settings(&self) -> &MasterEffects {
+        &self.settings
+    }
+
+    /// Build what switching to `settings` needs and count it as handed over
+    pub fn prepare(&mut self, settings: &MasterEffects, sample_rate: f32) -> EffectsUpdate {
+        // The reverb allocates its delay lines, so only build it while it's in use
+        let room = settings.reverb.room();
+        let rebuild = self.reverb_room != Some(room);
+        let unit = (settings.reverb.enabled && rebuild).then(|| {
+            self.reverb_room = Some(room);
+            new_reverb(room, sample_rate)
+        });
+
+        self.settings = *settings;
+        EffectsUpdate {
+            settings: *settings,
+            reverb: unit,
+        }
+    }
+
+    /// Everything handed over so far, built anew for a chain starting from nothing
+    pub fn rebuild(&self, sample_rate: f32) -> EffectsUpdate {
+        let mut update = EffectsPlan::default().prepare(&self.settings, sample_rate);
+        if let (None, Some(room)) = (&update.reverb, self.reverb_room) {
+            update.reverb = Some(new_reverb(room, sample_rate));
+        }
+        update
+    }
+}
+
+/// A change of master effects with anything it allocates already built
+pub struct EffectsUpdate {
+    settings: MasterEffects,
+    reverb: Option<Box<dyn AudioUnit>>, // Built for the new room, or None to keep the old one
+}
+
+impl EffectsUpdate {
+    /// Take over what `unapplied`, an earlier change that never reached the chain, built
+    pub fn absorb(&mut self, unapplied: EffectsUpdate) {
+        if self.reverb.is_none() {
+            self.reverb = unapplied.reverb;
+        }
+    }
+}
+
+/// Stereo reverb for `room`, allocated and ready to play
+pub(super) fn new_reverb(room: ReverbRoom, sample_rate: f32) -> Box<dyn AudioUnit> {
+    let mut unit: Box<dyn AudioUnit> = Box::new(reverb_stereo(
+        room.room_size.clamp(1.0, 100.0) as f64,
+        room.time.max(0.1) as f64,
+        room.damping.clamp(0.0, 1.0) as f64,
+    ));
+    unit.set_sample_rate(sample_rate as f64);
+    unit.allocate();
+    unit
+}
+
+fn new_limiter(sample_rate: f32) -> Box<dyn AudioUnit> {
+    let mut limiter: Box<dyn AudioUnit> = Box::new(limiter(LIMITER_ATTACK, LIMITER_RELEASE));
+    limiter.set_sample_rate(sample_rate as f64);
+    limiter.allocate();
+
+    // A new limiter's gain follower jumps to its first reading, but after `reset` it
+    // glides there. Run it past its look-ahead and reset it, so `EffectsChain::reset`
+    // starts exactly where a new chain does.
+    for _ in 0..(LIMITER_ATTACK * sample_rate) as usize + 2 {
+        limiter.filter_mono(0.0);
+    }
+    limiter.reset();
+    limiter
+}
+
+/// Shelf and bell filters whose parameters are read from shared values each sample,
+/// so they can change without resetting the filters
+struct Equalizer {
+    unit: Box<dyn AudioUnit>,
+    low_freq: Shared,
+    low_gain: Shared,
+    mid_freq: Shared,
+    mid_q: Shared,
+    mid_gain: Shared,
+    high_freq: Shared,
+    high_gain: Shared,
+}
+
+impl Equalizer {
+    fn new(sample_rate: f32) -> Self {
+        let low_freq = shared(100.0);
+        let low_gain = shared(1.0);
+        let mid_freq = shared(1000.0);
+        let mid_q = shared(0.7);
+        let mid_gain = shared(1.0);
+        let high_freq = shared(8000.0);
+        let high_gain = shared(1.0);
+
+        let mut unit: Box<dyn AudioUnit> = Box::new(
+            ((pass() | var(&low_freq) | dc(SHELF_Q) | var(&low_gain)) >> lowshelf::<f32>())
+                >> ((pass() | var(&mid_freq) | var(&mid_q) | var(&mid_gain)) >> bell::<f32>())
+                >> ((pass() | var(&high_freq) | dc(SHELF_Q) | var(&high_gain))
+                    >> highshelf::<f32>()),
+        );
+        unit.set_sample_rate(sample_rate as f64);
+
+        Equalizer {
+            unit,
+            low_freq,
+            low_gain,
+            mid_freq,
+            mid_q,
+            mid_gain,
+            high_freq,
+            high_gain,
+        }
+    }
+
+    fn set(&self, settings: &EqSettings, sample_rate: f32) {
+        // Keep every band safely below Nyquist
+        let max_freq = sample_rate * 0.45;
+        self.low_freq
+            .set_value(settings.low_freq.clamp(20.0, max_freq));
+        self.low_gain.set_value(db_amp(settings.low_gain_db));
+        self.mid_freq
+            .set_value(settings.mid_freq.clamp(20.0, max_freq));
+        self.mid_q.set_value(settings.mid_q.max(0.1));
+        self.mid_gain.set_value(db_amp(settings.mid_gain_db));
+        self.high_freq
+            .set_value(settings.high_freq.clamp(20.0, max_freq));
+        self.high_gain.set_value(db_amp(settings.high_gain_db));
+    }
+}
+
+struct Compressor {
+    threshold_db: f32,
+    slope: f32, // Gain reduction per dB over the threshold
+    makeup: f32,
+    attack_coeff: f32,
+    release_coeff: f32,
+    envelope: f32,
+}
+
+impl Compressor {
+    fn new() -> Self {
+        Compressor {
+            threshold_db: 0.0,
+            slope: 0.0,
+            makeup: 1.0,
+            attack_coeff: 0.0,
+            release_coeff: 0.0,
+            envelope: 0.0,
+        }
+    }
+
+    fn configure(&mut self, settings: &CompressorSettings, sample_rate: f32) {
+        self.threshold_db = settings.threshold_db;
+        self.slope = 1.0 - 1.0 / settings.ratio.max(1.0);
+        self.makeup = db_amp(settings.makeup_db);
+        self.attack_coeff = smoothing_coeff(settings.attack_ms, sample_rate);
+        self.release_coeff = smoothing_coeff(settings.release_ms, sample_rate);
+    }
+
+    fn process(&mut self, x: f32) -> f32 {
+        let level = x.abs();
+        let coeff = if level > self.envelope {
+            self.attack_coeff
+        } else {
+            self.release_coeff
+        };
+        self.envelope = level + coeff * (self.envelope - level);
+
+        let over_db = amp_db(self.envelope.max(1e-6)) - self.threshold_db;
+        let gain = if over_db > 0.0 {
+            db_amp(-over_db * self.slope)
+        } else {
+            1.0
+        };
+        x * gain * self.makeup
+    }
+}
+
+/// One-pole smoothing coefficient that gets about two thirds of the way in `time_ms`
+pub(super) fn smoothing_coeff(time_ms: f32, sample_rate: f32) -> f32 {
+    (-1.0 / (time_ms.max(0.1) * 0.001 * sample_rate)).exp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(frequency: f32, amplitude: f32, sample_rate: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| {
+                amplitude * (2.0 * std::f32::consts::PI * frequency * i as f32 / sample_rate).sin()
+            })
+            .collect()
+    }
+
+    /// Chain built from nothing for `effects`, as an output opening does
+    fn chain_for(effects: &MasterEffects) -> EffectsChain {
+        EffectsChain::prepared(EffectsPlan::default().prepare(effects, 48000.0), 48000.0)
+    }
+
+    fn peak(buffer: &[f32]) -> f32 {
+        buffer.iter().fold(0.0, |peak, x| peak.max(x.abs()))
+    }
+
+    #[test]
+    fn test_default_chain_leaves_audio_untouched() {
+        let effects = MasterEffects::default();
+        assert!(effects.is_bypassed());
+
+        let mut chain = chain_for(&effects);
+        let input = sine(440.0, 0.8, 48000.0, 1024);
+        let mut buffer = input.clone();
+        chain.process(&mut buffer);
+        assert_eq!(buffer, input);
+
+        println!("✅ Bypassed effects chain test passed");
+    }
+
+    #[test]
+    fn test_limiter_holds_the_ceiling() {
+        let mut effects = MasterEffects::default();
+        effects.limiter.enabled = true;
+        effects.limiter.ceiling_db = -6.0;
+
+        let mut chain = chain_for(&effects);
+        let mut buffer = sine(100.0, 1.8, 48000.0, 48000);
+        chain.process(&mut buffer);
+
+        let ceiling = db_amp(-6.0f32);
+        assert!(peak(&buffer) <= ceiling + 1e-6);
+        // Limiting, not silencing: once settled the signal sits near the ceiling
+        assert!(peak(&buffer[24000..]) > ceiling * 0.5);
+
+        println!("✅ Limiter ceiling test passed");
+    }
+
+    #[test]
+    fn test_compressor_reduces_loud_signals() {
+        let mut effects = MasterEffects::default();
+        effects.compressor.enabled = true;
+        effects.compressor.threshold_db = -20.0;
+        effects.compressor.ratio = 10.0;
+
+        let mut chain = chain_for(&effects);
+        let mut loud = sine(200.0, 1.0, 48000.0, 48000);
+        chain.process(&mut loud);
+        assert!(peak(&loud[24000..]) < 0.3);
+
+        // Below the threshold nothing changes
+        let mut chain = chain_for(&effects);
+        let quiet = sine(200.0, 0.05, 48000.0, 4800);
+        let mut buffer = quiet.clone();
+        chain.process(&mut buffer);
+        assert_eq!(buffer, quiet);
+
+        println!("✅ Compressor test passed");
+    }
+
+    #[test]
+    fn test_reverb_leaves_a_tail() {
+        let mut effects = MasterEffects::default();
+        effects.reverb.enabled = true;
+        effects.reverb.mix = 0.5;
+
+        let mut chain = chain_for(&effects);
+        let mut buffer = vec![0.0f32; 48000];
+        buffer[0] = 1.0;
+        chain.process(&mut buffer);
+
+        // The dry impulse is halved, and the room keeps ringing after it
+        assert!((buffer[0] - 0.5).abs() < 0.1);
+        assert!(peak(&buffer[4800..]) > 1e-4);
+
+        // Changing only the mix keeps the same reverb, tail and all
+        let mut plan = EffectsPlan::default();
+        plan.prepare(&effects, 48000.0);
+        effects.reverb.mix = 0.3;
+        let mut update = plan.prepare(&effects, 48000.0);
+        assert!(update.reverb.is_none());
+        chain.apply(&mut update);
+        let mut tail = vec![0.0f32; 480];
+        chain.process(&mut tail);
+        assert!(peak(&tail) > 1e-4);
+
+        // A new room is built before it reaches the chain
+        effects.reverb.room_size = 40.0;
+        assert!(plan.prepare(&effects, 48000.0).reverb.is_some());
+
+        println!("✅ Reverb tail test passed");
+    }
+
+    #[test]
+    fn test_eq_boosts_and_cuts_bands() {
+        let mut effects = MasterEffects::default();
+        effects.eq.enabled = true;
+        effects.eq.low_gain_db = 12.0;
+        effects.eq.high_gain_db = -12.0;
+
+        let mut chain = chain_for(&effects);
+        let mut low = sine(40.0, 0.1, 48000.0, 48000);
+        chain.process(&mut low);
+        assert!(peak(&low[24000..]) > 0.2);
+
+        let mut chain = chain_for(&effects);
+        let mut high = sine(15000.0, 0.1, 48000.0, 48000);
+        chain.process(&mut high);
+        assert!(peak(&high[24000..]) < 0.05);
+
+        println!("✅ EQ bands test passed");
+    }
+
+    #[test]
+    fn test_reset_chain_sounds_like_a_new_one() {
+        let mut effects = MasterEffects::default();
+        effects.eq.enabled = true;
+        effects.eq.mid_gain_db = 6.0;
+        effects.compressor.enabled = true;
+        effects.reverb.enabled = true;
+        effects.limiter.enabled = true;
+        let input = sine(220.0, 1.5, 48000.0, 9600);
+
+        let mut chain = chain_for(&effects);
+        let mut first = input.clone();
+        chain.process(&mut first);
+
+        chain.reset();
+        let mut second = input.clone();
+        chain.process(&mut second);
+        assert_eq!(first, second);
+
+        println!("✅ Effects chain reset test passed");
+    }
+
+    #[test]
+    fn test_effects_load_with_missing_fields() {
+        let effects: MasterEffects =
+            serde_json::from_str(r#"{"reverb": {"enabled": true}}"#).unwrap();
+        assert!(effects.reverb.enabled);
+        assert_eq!(effects.reverb.mix, ReverbSettings::default().mix);
+        assert_eq!(effects.eq, EqSettings::default());
+
+        println!("✅ Effects deserialization test passed");
+    }
+}
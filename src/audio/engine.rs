@@ -1,10 +1,14 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use cpal::{
     traits::{DeviceTrait, HostTrait, StreamTrait},
     Device, Host, SampleFormat, Stream, StreamConfig,
 };
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 
+use super::backend::{AudioBackend, RenderFn, TimerSink};
+use super::effects::{EffectsChain, EffectsPlan, EffectsUpdate, MasterEffects};
+use super::mixer::{Mixer, MixerPlan, MixerSettings, MixerUpdate};
 use super::SampleBank;
 use crate::settings::AudioSettings;
 use crate::timeline::Timeline;
@@ -41,14 +45,123 @@ pub enum DeviceRecoveryAction {
     DeviceUnavailable,
 }
 
-pub struct AudioEngine {
-    _host: Host,
-    _device: Device,
-    _stream: Stream,
+/// Where the engine's audio goes, kept alive for as long as it plays
+enum Output {
+    Device { _device: Device, stream: Stream },
+    Timer(TimerSink),
+}
+
+impl Output {
+    fn play(&self) -> Result<()> {
+        match self {
+            Output::Device { stream, .. } => stream.play()?,
+            Output::Timer(sink) => sink.play(),
+        }
+        Ok(())
+    }
+
+    fn pause(&self) {
+        match self {
+            Output::Device { stream, .. } => {
+                let _ = stream.pause();
+            }
+            Output::Timer(sink) => sink.pause(),
+        }
+    }
+}
+
+/// State the engine shares with the render function of whichever output is playing
+#[derive(Clone)]
+struct SharedState {
     sample_bank: Arc<Mutex<SampleBank>>,
     timeline: Arc<Mutex<Timeline>>,
-    sample_rate: f32,
     master_volume: Arc<Mutex<f32>>,
+    master_effects: Arc<Mutex<Handoff<EffectsPlan, EffectsUpdate>>>,
+    mixer: Arc<Mutex<Handoff<MixerPlan, MixerUpdate>>>,
+}
+
+impl SharedState {
+    /// Build what `effects` need and queue them for the callback
+    fn set_master_effects(&self, effects: &MasterEffects, sample_rate: f32) {
+        let mut handoff = self.master_effects.lock().unwrap();
+        if handoff.plan.settings() != effects {
+            let update = handoff.plan.prepare(effects, sample_rate);
+            handoff.send(update, EffectsUpdate::absorb);
+        }
+    }
+
+    /// Build what `mixer` needs and queue it for the callback
+    fn set_mixer(&self, mixer: &MixerSettings, sample_rate: f32) {
+        let mut handoff = self.mixer.lock().unwrap();
+        if handoff.plan.settings() != mixer {
+            let update = handoff.plan.prepare(mixer, sample_rate);
+            handoff.send(update, MixerUpdate::absorb);
+        }
+    }
+
+    /// Queue everything handed over so far again, for an output that missed changes
+    /// a newer one picked up
+    fn resend(&self, sample_rate: f32) {
+        let mut handoff = self.master_effects.lock().unwrap();
+        let update = handoff.plan.rebuild(sample_rate);
+        handoff.send(update, EffectsUpdate::absorb);
+
+        let mut handoff = self.mixer.lock().unwrap();
+        let update = handoff.plan.rebuild(sample_rate);
+        handoff.send(update, MixerUpdate::absorb);
+    }
+}
+
+/// Changes to part of the audio path on their way to the callback. Whatever a change
+/// allocates is built by the thread making it, so the callback only swaps parts in.
+struct Handoff<P, U> {
+    plan: P,           // What the callback has once the pending update is applied
+    update: Option<U>, // Once applied, holds what it replaced until the next change frees it
+    pending: bool,
+}
+
+impl<P, U> Handoff<P, U> {
+    fn new(plan: P) -> Self {
+        Handoff {
+            plan,
+            update: None,
+            pending: false,
+        }
+    }
+
+    /// Queue `update`. One the callback hasn't picked up yet is merged in, since the
+    /// plan already counts on what it built.
+    fn send(&mut self, mut update: U, absorb: fn(&mut U, U)) {
+        if let Some(unapplied) = self.update.take().filter(|_| self.pending) {
+            absorb(&mut update, unapplied);
+        }
+        self.update = Some(update);
+        self.pending = true;
+    }
+
+    /// Apply the pending update, if there is one. Called from the audio callback.
+    fn receive(&mut self, apply: impl FnOnce(&mut U)) {
+        if self.pending {
+            if let Some(update) = self.update.as_mut() {
+                apply(update);
+            }
+            self.pending = false;
+        }
+    }
+
+    /// Drop the queued update, for a callback built from a plan that already includes it
+    fn clear(&mut self) {
+        self.update = None;
+        self.pending = false;
+    }
+}
+
+pub struct AudioEngine {
+    _host: Host,
+    output: Output,
+    shared: SharedState,
+    sample_rate: f32,
+    output_latency: Arc<Mutex<f64>>, // Seconds from rendering a buffer to hearing its end
     current_device_name: String,
     settings: AudioSettings,
 }
@@ -63,151 +176,151 @@ impl AudioEngine {
     pub fn new_with_settings(settings: AudioSettings) -> Result<Self> {
         let host = cpal::default_host();
 
-        // Device selection based on settings
-        let device = if let Some(preferred_device) = &settings.preferred_device {
-            // Try to find the preferred device
-            let devices = host.output_devices()?;
-            let mut found_device = None;
-            for device in devices {
-                if let Ok(name) = device.name() {
-                    if name == *preferred_device {
-                        found_device = Some(device);
-                        break;
-                    }
-                }
-            }
+        // Device selection based on settings, falling back to the default device
+        let mut output_name = Self::output_name(&settings);
+        if settings.backend == AudioBackend::Device
+            && Self::find_output_device(&host, &output_name).is_err()
+        {
+            eprintln!(
+                "Warning: Preferred device '{}' not found, using default",
+                output_name
+            );
+            output_name = "Default Device".to_string();
+        }
 
-            // Fall back to default if preferred device not found
-            found_device.unwrap_or_else(|| {
-                eprintln!(
-                    "Warning: Preferred device '{}' not found, using default",
-                    preferred_device
-                );
-                host.default_output_device()
-                    .expect("No output device available")
-            })
-        } else {
-            host.default_output_device()
-                .ok_or_else(|| anyhow::anyhow!("No output device available"))?
+        let shared = SharedState {
+            sample_bank: Arc::new(Mutex::new({
+                let mut bank = SampleBank::new();
+                bank.load_default_samples();
+                bank
+            })),
+            timeline: Arc::new(Mutex::new(Timeline::new())),
+            master_volume: Arc::new(Mutex::new(settings.master_volume)),
+            master_effects: Arc::new(Mutex::new(Handoff::new(EffectsPlan::default()))),
+            mixer: Arc::new(Mutex::new(Handoff::new(MixerPlan::default()))),
         };
+        let output_latency = Arc::new(Mutex::new(buffer_latency(&settings)));
 
-        // Get default config and override with settings
-        let default_config = device.default_output_config()?;
-        let sample_rate = cpal::SampleRate(settings.sample_rate);
-        let channels = 1; // Force mono output for simpler timing
+        let output = Self::open_output(&host, &output_name, &settings, &shared, &output_latency)?;
+        output.play()?;
 
-        println!("🎵 Audio Engine Configuration:");
-        println!(
-            "  Sample Rate: {} Hz (configured: {})",
-            sample_rate.0, settings.sample_rate
-        );
-        println!("  Buffer Size: {} samples", settings.buffer_size);
-        println!("  Master Volume: {:.0}%", settings.master_volume * 100.0);
-        println!("  Channels: {} (forced mono)", channels);
-        println!("  Sample Format: {:?}", default_config.sample_format());
-        println!(
-            "  Device: {}",
-            device.name().unwrap_or_else(|_| "Unknown".to_string())
-        );
+        Ok(AudioEngine {
+            _host: host,
+            output,
+            shared,
+            sample_rate: settings.sample_rate as f32,
+            output_latency,
+            current_device_name: output_name,
+            settings,
+        })
+    }
 
-        let sample_bank = Arc::new(Mutex::new({
-            let mut bank = SampleBank::new();
-            bank.load_default_samples();
-            bank
-        }));
-        let timeline = Arc::new(Mutex::new(Timeline::new()));
-        let master_volume = Arc::new(Mutex::new(settings.master_volume));
+    /// Name of the output `settings` ask for: the preferred device, or the backend's name
+    fn output_name(settings: &AudioSettings) -> String {
+        match settings.backend {
+            AudioBackend::Device => settings
+                .preferred_device
+                .clone()
+                .unwrap_or_else(|| "Default Device".to_string()),
+            ref backend => backend.display_name(),
+        }
+    }
 
-        let sample_bank_clone = Arc::clone(&sample_bank);
-        let timeline_clone = Arc::clone(&timeline);
-        let master_volume_clone = Arc::clone(&master_volume);
+    /// Find an output device by name; "Default Device" and "(Default)" names mean the default
+    fn find_output_device(host: &Host, device_name: &str) -> Result<Device> {
+        if device_name == "Default Device" || device_name.ends_with(" (Default)") {
+            return host
+                .default_output_device()
+                .ok_or_else(|| anyhow::anyhow!("No output device available"));
+        }
 
-        let stream_config = StreamConfig {
-            channels,
-            sample_rate,
-            buffer_size: cpal::BufferSize::Fixed(settings.buffer_size),
-        };
+        host.output_devices()?
+            .find(|device| device.name().is_ok_and(|name| name == device_name))
+            .ok_or_else(|| anyhow::anyhow!("Device '{}' not found", device_name))
+    }
 
-        let stream = match default_config.sample_format() {
-            SampleFormat::F32 => device.build_output_stream(
-                &stream_config,
-                move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
-                    audio_callback(
-                        data,
-                        &sample_bank_clone,
-                        &timeline_clone,
-                        &master_volume_clone,
-                        sample_rate.0 as f32,
-                    )
-                },
-                |err| eprintln!("Audio stream error: {}", err),
-                None,
-            )?,
-            SampleFormat::I16 => device.build_output_stream(
-                &stream_config,
-                move |data: &mut [i16], _: &cpal::OutputCallbackInfo| {
-                    let mut f32_data = vec![0.0f32; data.len()];
-                    audio_callback(
-                        &mut f32_data,
-                        &sample_bank_clone,
-                        &timeline_clone,
-                        &master_volume_clone,
-                        sample_rate.0 as f32,
-                    );
-                    for (i, sample) in f32_data.iter().enumerate() {
-                        data[i] = (*sample * i16::MAX as f32) as i16;
-                    }
-                },
-                |err| eprintln!("Audio stream error: {}", err),
-                None,
-            )?,
-            SampleFormat::U16 => device.build_output_stream(
-                &stream_config,
-                move |data: &mut [u16], _: &cpal::OutputCallbackInfo| {
-                    let mut f32_data = vec![0.0f32; data.len()];
-                    audio_callback(
-                        &mut f32_data,
-                        &sample_bank_clone,
-                        &timeline_clone,
-                        &master_volume_clone,
-                        sample_rate.0 as f32,
-                    );
-                    for (i, sample) in f32_data.iter().enumerate() {
-                        data[i] = ((*sample + 1.0) * 0.5 * u16::MAX as f32) as u16;
-                    }
-                },
-                |err| eprintln!("Audio stream error: {}", err),
-                None,
-            )?,
-            _ => return Err(anyhow::anyhow!("Unsupported sample format")),
-        };
+    /// Open (but do not start) the output selected by `settings.backend`, rendering the timeline
+    fn open_output(
+        host: &Host,
+        device_name: &str,
+        settings: &AudioSettings,
+        shared: &SharedState,
+        output_latency: &Arc<Mutex<f64>>,
+    ) -> Result<Output> {
+        let render = renderer(shared, settings.sample_rate as f32);
+
+        match &settings.backend {
+            AudioBackend::Device => {
+                let device = Self::find_output_device(host, device_name)?;
+                let stream = build_output_stream(&device, settings, render, output_latency)?;
+                Ok(Output::Device {
+                    _device: device,
+                    stream,
+                })
+            }
+            AudioBackend::Null => Ok(Output::Timer(TimerSink::null(
+                render,
+                settings.sample_rate,
+                settings.buffer_size,
+            )?)),
+            AudioBackend::File(path) => Ok(Output::Timer(TimerSink::file(
+                render,
+                settings.sample_rate,
+                settings.buffer_size,
+                path,
+            )?)),
+        }
+    }
 
-        stream.play()?;
+    /// Replace the output with one on `device_name` using `settings`.
+    /// The timeline, samples and volume are shared, so playback carries on from the
+    /// same position. If the new output cannot be started the old one keeps playing.
+    fn rebuild_stream(&mut self, device_name: &str, settings: AudioSettings) -> Result<()> {
+        // Stop the old callback so the two outputs never advance the timeline together.
+        // A WAV file that is about to be recreated has to be closed first.
+        let same_file = matches!(settings.backend, AudioBackend::File(_))
+            && settings.backend == self.settings.backend;
+        match &mut self.output {
+            Output::Timer(sink) if same_file => sink.stop(),
+            output => output.pause(),
+        }
 
-        let current_device_name = settings
-            .preferred_device
-            .clone()
-            .unwrap_or_else(|| "Default Device".to_string());
+        let output = Self::open_output(
+            &self._host,
+            device_name,
+            &settings,
+            &self.shared,
+            &self.output_latency,
+        )
+        .and_then(|output| {
+            output.play()?;
+            Ok(output)
+        });
+        let output = match output {
+            Ok(output) => output,
+            Err(e) => {
+                // The failed output took over the changes still on their way
+                self.shared.resend(self.sample_rate);
+                let _ = self.output.play();
+                return Err(e);
+            }
+        };
 
-        Ok(AudioEngine {
-            _host: host,
-            _device: device,
-            _stream: stream,
-            sample_bank,
-            timeline,
-            sample_rate: sample_rate.0 as f32,
-            master_volume,
-            current_device_name,
-            settings,
-        })
+        self.output = output;
+        // Devices measure their latency as they play; until then assume one buffer
+        *self.output_latency.lock().unwrap() = buffer_latency(&settings);
+        self.sample_rate = settings.sample_rate as f32;
+        self.current_device_name = device_name.to_string();
+        self.settings = settings;
+        Ok(())
     }
 
     pub fn sample_bank(&self) -> Arc<Mutex<SampleBank>> {
-        Arc::clone(&self.sample_bank)
+        Arc::clone(&self.shared.sample_bank)
     }
 
     pub fn timeline(&self) -> Arc<Mutex<Timeline>> {
-        Arc::clone(&self.timeline)
+        Arc::clone(&self.shared.timeline)
     }
 
     pub fn sample_rate(&self) -> f32 {
@@ -216,18 +329,59 @@ impl AudioEngine {
 
     /// Get the current master volume
     pub fn get_master_volume(&self) -> f32 {
-        *self.master_volume.lock().unwrap()
+        *self.shared.master_volume.lock().unwrap()
     }
 
     /// Set the master volume (0.0 to 2.0)
     pub fn set_master_volume(&self, volume: f32) {
         let clamped_volume = volume.clamp(0.0, 2.0);
-        *self.master_volume.lock().unwrap() = clamped_volume;
+        *self.shared.master_volume.lock().unwrap() = clamped_volume;
+    }
+
+    /// Seconds between the audio callback rendering a buffer (advancing the timeline) and
+    /// the end of that buffer being heard: the buffer itself plus the device's delay.
+    /// Subtract it from the timeline position to get what is playing right now.
+    pub fn output_latency(&self) -> f64 {
+        *self.output_latency.lock().unwrap()
     }
 
     /// Get the master volume reference for sharing with audio callback
     pub fn master_volume(&self) -> Arc<Mutex<f32>> {
-        Arc::clone(&self.master_volume)
+        Arc::clone(&self.shared.master_volume)
+    }
+
+    /// Change the master effects; playing outputs pick them up with the next buffer
+    pub fn set_master_effects(&self, effects: MasterEffects) {
+        self.shared.set_master_effects(&effects, self.sample_rate);
+    }
+
+    /// Change the track effects and send buses; playing outputs pick them up with the next buffer
+    pub fn set_mixer(&self, mixer: MixerSettings) {
+        self.shared.set_mixer(&mixer, self.sample_rate);
+    }
+
+    /// Render the timeline from the start, with the track effects, master volume and
+    /// master effects playback uses, to a mono WAV file at the output's sample rate
+    pub fn export_wav(&self, path: &Path) -> Result<()> {
+        let timeline = self.shared.timeline.lock().unwrap().clone();
+        if timeline.segments.is_empty() {
+            return Err(anyhow::anyhow!("The timeline has no segments to export"));
+        }
+        let audio = render_offline(&self.shared, timeline, self.sample_rate);
+
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: self.sample_rate as u32,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        let mut writer = hound::WavWriter::create(path, spec)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        for sample in audio {
+            writer.write_sample(sample)?;
+        }
+        writer.finalize()?;
+        Ok(())
     }
 
     /// Get list of available audio output devices
@@ -469,7 +623,10 @@ impl AudioEngine {
 
     /// Monitor current device availability (for instance method)
     pub fn monitor_device_availability(&self) -> Result<bool> {
-        if self.settings.device_monitoring_enabled {
+        if self.settings.backend != AudioBackend::Device {
+            // Null and file outputs cannot be unplugged
+            Ok(true)
+        } else if self.settings.device_monitoring_enabled {
             Ok(Self::is_device_available(&self.current_device_name))
         } else {
             // If monitoring is disabled, assume device is available
@@ -484,7 +641,8 @@ impl AudioEngine {
 
     /// Handle device disconnection and fallback
     pub fn handle_device_disconnection(&mut self) -> Result<DeviceRecoveryAction> {
-        if !self.settings.device_monitoring_enabled {
+        if !self.settings.device_monitoring_enabled || self.settings.backend != AudioBackend::Device
+        {
             return Ok(DeviceRecoveryAction::NoAction);
         }
 
@@ -533,41 +691,258 @@ impl AudioEngine {
         Ok(())
     }
 
-    /// Switch to a different device (for fallback scenarios)
+    /// Switch to a different device (for fallback scenarios), rebuilding the stream
+    /// with the current sample rate and buffer size. Returns false if the device is
+    /// not available, and an error if its stream could not be started.
     pub fn switch_to_device(&mut self, new_device_name: String) -> Result<bool> {
-        // This would require recreating the audio stream with the new device
-        // For now, we'll just update the tracking and return success if the device is available
-        if Self::is_device_available(&new_device_name) {
-            self.current_device_name = new_device_name.clone();
-            self.settings.preferred_device = if new_device_name == "Default Device" {
-                None
-            } else {
-                Some(new_device_name)
-            };
-            Ok(true)
+        if !Self::is_device_available(&new_device_name) {
+            return Ok(false);
+        }
+
+        let mut settings = self.settings.clone();
+        settings.backend = AudioBackend::Device;
+        settings.preferred_device = if new_device_name == "Default Device" {
+            None
         } else {
-            Ok(false)
+            Some(new_device_name.clone())
+        };
+        self.rebuild_stream(&new_device_name, settings)?;
+        Ok(true)
+    }
+
+    /// Apply new sample rate and buffer size settings by rebuilding the stream on the
+    /// current device
+    pub fn reconfigure(&mut self, sample_rate: u32, buffer_size: u32) -> Result<()> {
+        let mut settings = self.settings.clone();
+        settings.sample_rate = sample_rate;
+        settings.buffer_size = buffer_size;
+        let device_name = self.current_device_name.clone();
+        self.rebuild_stream(&device_name, settings)
+    }
+
+    /// Settings the engine is currently running with
+    pub fn settings(&self) -> &AudioSettings {
+        &self.settings
+    }
+
+    /// Apply changed audio settings while running. The stream is only rebuilt when the
+    /// backend, device, sample rate or buffer size changed. On failure the previous
+    /// stream keeps playing, or the last known good device takes over if the previous
+    /// one is gone.
+    pub fn apply_settings(&mut self, settings: AudioSettings) -> Result<()> {
+        self.set_master_volume(settings.master_volume);
+
+        let device_name = Self::output_name(&settings);
+        let last_known_good_device = self.settings.last_known_good_device.clone();
+        let stream_changed = settings.backend != self.settings.backend
+            || device_name != self.current_device_name
+            || settings.sample_rate != self.settings.sample_rate
+            || settings.buffer_size != self.settings.buffer_size;
+
+        if !stream_changed {
+            self.settings = AudioSettings {
+                last_known_good_device,
+                ..settings
+            };
+            return Ok(());
+        }
+
+        let is_device = settings.backend == AudioBackend::Device;
+        match self.rebuild_stream(&device_name, settings) {
+            Ok(()) => {
+                self.settings.last_known_good_device = if is_device {
+                    Some(device_name)
+                } else {
+                    last_known_good_device
+                };
+                Ok(())
+            }
+            Err(e) => {
+                if self.settings.backend == AudioBackend::Device
+                    && !Self::is_device_available(&self.current_device_name)
+                {
+                    if let Some(good_device) = last_known_good_device {
+                        let mut fallback = self.settings.clone();
+                        fallback.preferred_device =
+                            Some(good_device.clone()).filter(|name| name != "Default Device");
+                        if let Err(fallback_error) = self.rebuild_stream(&good_device, fallback) {
+                            eprintln!(
+                                "Failed to fall back to last known good device '{}': {}",
+                                good_device, fallback_error
+                            );
+                        }
+                    }
+                }
+                Err(e)
+            }
         }
     }
 }
 
+/// Render function for one output. Each output starts with fresh playback state and
+/// picks up the timeline position.
+fn renderer(shared: &SharedState, sample_rate: f32) -> RenderFn {
+    let shared = shared.clone();
+    let mut state = CallbackState::new(&shared, sample_rate);
+
+    Box::new(move |data: &mut [f32]| audio_callback(data, &mut state, &shared, sample_rate))
+}
+
+// Buffer size of offline renders; any size renders the same audio
+const OFFLINE_BUFFER_SIZE: usize = 1024;
+// Longest effect tails may ring on after the timeline ends in an offline render
+const OFFLINE_MAX_TAIL_SECONDS: f32 = 10.0;
+// Peak below which a rendered buffer counts as silent
+const OFFLINE_SILENCE: f32 = 1e-5;
+
+/// Play `timeline` once from the start through a callback of its own, exactly as an
+/// output would, then keep rendering until the effect tails die away. The loop region
+/// is ignored.
+fn render_offline(shared: &SharedState, mut timeline: Timeline, sample_rate: f32) -> Vec<f32> {
+    timeline.stop();
+    timeline.clear_loop_region();
+    timeline.play();
+
+    let effects_plan = shared.master_effects.lock().unwrap().plan;
+    let mixer_plan = shared.mixer.lock().unwrap().plan.clone();
+    let shared = SharedState {
+        sample_bank: Arc::clone(&shared.sample_bank),
+        timeline: Arc::new(Mutex::new(timeline)),
+        master_volume: Arc::new(Mutex::new(*shared.master_volume.lock().unwrap())),
+        master_effects: Arc::new(Mutex::new(Handoff::new(effects_plan))),
+        mixer: Arc::new(Mutex::new(Handoff::new(mixer_plan))),
+    };
+    let mut state = CallbackState::new(&shared, sample_rate);
+
+    let mut output = Vec::new();
+    let mut buffer = vec![0.0f32; OFFLINE_BUFFER_SIZE];
+    while shared.timeline.lock().unwrap().is_playing() {
+        audio_callback(&mut buffer, &mut state, &shared, sample_rate);
+        output.extend_from_slice(&buffer);
+    }
+
+    let max_tail = (OFFLINE_MAX_TAIL_SECONDS * sample_rate) as usize;
+    let mut tail = 0;
+    while tail < max_tail {
+        audio_callback(&mut buffer, &mut state, &shared, sample_rate);
+        if buffer.iter().all(|x| x.abs() < OFFLINE_SILENCE) {
+            break;
+        }
+        output.extend_from_slice(&buffer);
+        tail += buffer.len();
+    }
+    output
+}
+
+/// Time to play one buffer, the latency of outputs that cannot measure their own
+fn buffer_latency(settings: &AudioSettings) -> f64 {
+    settings.buffer_size as f64 / settings.sample_rate as f64
+}
+
+/// Build (but do not start) a mono output stream on `device` that plays `render`,
+/// measuring the output latency from the callback timestamps
+fn build_output_stream(
+    device: &Device,
+    settings: &AudioSettings,
+    mut render: RenderFn,
+    output_latency: &Arc<Mutex<f64>>,
+) -> Result<Stream> {
+    // Get default config and override with settings
+    let default_config = device.default_output_config()?;
+    let sample_rate = cpal::SampleRate(settings.sample_rate);
+    let channels = 1; // Force mono output for simpler timing
+
+    println!("🎵 Audio Engine Configuration:");
+    println!(
+        "  Sample Rate: {} Hz (configured: {})",
+        sample_rate.0, settings.sample_rate
+    );
+    println!("  Buffer Size: {} samples", settings.buffer_size);
+    println!("  Master Volume: {:.0}%", settings.master_volume * 100.0);
+    println!("  Channels: {} (forced mono)", channels);
+    println!("  Sample Format: {:?}", default_config.sample_format());
+    println!(
+        "  Device: {}",
+        device.name().unwrap_or_else(|_| "Unknown".to_string())
+    );
+
+    let stream_config = StreamConfig {
+        channels,
+        sample_rate,
+        buffer_size: cpal::BufferSize::Fixed(settings.buffer_size),
+    };
+
+    // The buffer is heard from its playback timestamp on, and it ends one buffer later
+    let output_latency = Arc::clone(output_latency);
+    let measure_latency = move |frames: usize, info: &cpal::OutputCallbackInfo| {
+        let timestamp = info.timestamp();
+        let device_delay = timestamp
+            .playback
+            .duration_since(&timestamp.callback)
+            .unwrap_or_default();
+        *output_latency.lock().unwrap() =
+            frames as f64 / sample_rate.0 as f64 + device_delay.as_secs_f64();
+    };
+
+    let stream = match default_config.sample_format() {
+        SampleFormat::F32 => device.build_output_stream(
+            &stream_config,
+            move |data: &mut [f32], info: &cpal::OutputCallbackInfo| {
+                measure_latency(data.len(), info);
+                render(data)
+            },
+            |err| eprintln!("Audio stream error: {}", err),
+            None,
+        )?,
+        SampleFormat::I16 => device.build_output_stream(
+            &stream_config,
+            move |data: &mut [i16], info: &cpal::OutputCallbackInfo| {
+                measure_latency(data.len(), info);
+                let mut f32_data = vec![0.0f32; data.len()];
+                render(&mut f32_data);
+                for (i, sample) in f32_data.iter().enumerate() {
+                    data[i] = (*sample * i16::MAX as f32) as i16;
+                }
+            },
+            |err| eprintln!("Audio stream error: {}", err),
+            None,
+        )?,
+        SampleFormat::U16 => device.build_output_stream(
+            &stream_config,
+            move |data: &mut [u16], info: &cpal::OutputCallbackInfo| {
+                measure_latency(data.len(), info);
+                let mut f32_data = vec![0.0f32; data.len()];
+                render(&mut f32_data);
+                for (i, sample) in f32_data.iter().enumerate() {
+                    data[i] = ((*sample + 1.0) * 0.5 * u16::MAX as f32) as u16;
+                }
+            },
+            |err| eprintln!("Audio stream error: {}", err),
+            None,
+        )?,
+        _ => return Err(anyhow::anyhow!("Unsupported sample format")),
+    };
+
+    Ok(stream)
+}
+
 // Audio processing state - moved outside callback to avoid allocations
 pub struct AudioState {
-    pub current_step: usize,
-    samples_per_step: usize,
-    sample_counter: usize,
+    pub current_step: usize, // Steps since the segment started; patterns wrap it themselves
+    samples_per_step: f64,   // Fractional, so steps stay locked to musical time
+    step_phase: f64,         // Samples from the start of the current step to the next sample
     voices: Vec<Voice>,
-    loop_length: usize,
+    mixer: Mixer, // Track inserts and send buses the voices play through
 }
 
 impl AudioState {
     pub fn new(sample_rate: f32, bpm: f32) -> Self {
         let mut state = AudioState {
             current_step: 0,
-            samples_per_step: 0,
-            sample_counter: 0,
+            samples_per_step: 0.0,
+            step_phase: 0.0,
             voices: Vec::new(),
-            loop_length: 16,
+            mixer: Mixer::new(sample_rate),
         };
 
         state.update_timing(sample_rate, bpm);
@@ -583,7 +958,7 @@ impl AudioState {
     fn reset(&mut self) {
         // Reset all step counters and sample positions
         self.current_step = 0;
-        self.sample_counter = 0;
+        self.step_phase = 0.0;
 
         // Clear all voice states
         for voice in &mut self.voices {
@@ -607,22 +982,23 @@ impl AudioState {
         // Calculate which step we should be on based on position within segment
         let beats_per_second = bpm as f64 / 60.0;
         let steps_per_second = beats_per_second * 4.0; // 16th notes
-        let total_steps_elapsed = (position_within_segment * steps_per_second) as usize;
+        let steps_elapsed = position_within_segment.max(0.0) * steps_per_second;
+        let whole_steps = steps_elapsed.floor();
 
-        // Set current step based on position within the pattern loop
-        self.current_step = total_steps_elapsed % self.loop_length;
+        self.current_step = whole_steps as usize;
 
-        // Reset sample counter to start of current step
-        self.sample_counter = 0;
+        // Pick up partway through the step; it only triggers if it starts on this sample
+        self.step_phase = (steps_elapsed - whole_steps) * self.samples_per_step;
     }
 
     fn update_timing(&mut self, sample_rate: f32, bpm: f32) {
-        let beats_per_second = bpm / 60.0;
+        // Same f64 arithmetic as the timeline's segment durations
+        let beats_per_second = bpm as f64 / 60.0;
         let steps_per_second = beats_per_second * 4.0; // 16th notes
-        self.samples_per_step = (sample_rate / steps_per_second) as usize;
+        self.samples_per_step = sample_rate as f64 / steps_per_second;
     }
 
-    fn process_patterns(
+    pub(crate) fn process_patterns(
         &mut self,
         output: &mut [f32],
         sample_bank: &SampleBank,
@@ -631,38 +1007,72 @@ impl AudioState {
         sample_rate: f32,
     ) {
         // Update timing if BPM changed
-        if self.samples_per_step == 0 {
+        if self.samples_per_step == 0.0 {
             self.update_timing(sample_rate, bpm);
         }
 
         let mut sample_index = 0;
         while sample_index < output.len() {
-            // Check if we need to trigger step
-            if self.sample_counter == 0 {
+            // Trigger on the first sample at or after the exact start of the step
+            if self.step_phase < 1.0 {
                 self.trigger_current_step(sample_bank, patterns);
             }
 
             // Calculate how many samples to process in this iteration
-            let samples_until_next_step = self.samples_per_step - self.sample_counter;
+            let samples_until_next_step = (self.samples_per_step - self.step_phase).ceil() as usize;
             let samples_to_process = (output.len() - sample_index).min(samples_until_next_step);
 
             // Process voices for this chunk
             let chunk = &mut output[sample_index..sample_index + samples_to_process];
+            self.process_voices(chunk, sample_bank);
+
+            sample_index += samples_to_process;
+            self.step_phase += samples_to_process as f64;
+
+            // Advance step if needed, carrying the fraction of a sample over
+            if self.step_phase >= self.samples_per_step {
+                self.advance_step();
+                self.step_phase -= self.samples_per_step;
+            }
+        }
+    }
+
+    fn process_voices(&mut self, output: &mut [f32], sample_bank: &SampleBank) {
+        if self.mixer.is_idle() {
             for voice in &mut self.voices {
                 // Voice processing handles sample rate internally via direct indexing
                 // Sample data is pre-generated at the correct sample rate in SampleBank
-                voice.process(chunk, sample_bank);
+                voice.process(output, sample_bank);
             }
+            return;
+        }
 
-            sample_index += samples_to_process;
-            self.sample_counter += samples_to_process;
-
-            // Advance step if needed
-            if self.sample_counter >= self.samples_per_step {
-                self.advance_step();
-                self.sample_counter = 0;
+        // Voices of tracks with effects play into their track, the rest straight out
+        self.mixer.begin(output.len());
+        for voice in &mut self.voices {
+            match self.mixer.track_buffer(&voice.sample_name) {
+                Some(track) => voice.process(track, sample_bank),
+                None => voice.process(output, sample_bank),
             }
         }
+        self.mixer.finish(output);
+    }
+
+    /// Let track effects and send buses ring out while no voices play
+    fn process_effect_tails(&mut self, output: &mut [f32]) {
+        if !self.mixer.is_idle() {
+            self.mixer.begin(output.len());
+            self.mixer.finish(output);
+        }
+    }
+
+    pub(crate) fn apply_mixer(&mut self, update: &mut MixerUpdate) {
+        self.mixer.apply(update);
+    }
+
+    /// Silence track effects and send buses
+    fn reset_effects(&mut self) {
+        self.mixer.reset();
     }
 
     fn trigger_current_step(
@@ -671,8 +1081,7 @@ impl AudioState {
         patterns: &[super::sequencer::Pattern],
     ) {
         for pattern in patterns {
-            if self.current_step < pattern.steps.len() {
-                let step = pattern.steps[self.current_step];
+            if let Some(step) = pattern.step_in_loop(self.current_step) {
                 if step.active {
                     // Find available voice
                     if let Some(voice) = self.voices.iter_mut().find(|v| !v.active) {
@@ -684,7 +1093,7 @@ impl AudioState {
     }
 
     fn advance_step(&mut self) {
-        self.current_step = (self.current_step + 1) % self.loop_length;
+        self.current_step += 1;
     }
 }
 
@@ -743,16 +1152,51 @@ impl Voice {
     }
 }
 
-// Static audio state and timeline state tracking
-static mut AUDIO_STATE: Option<AudioState> = None;
-static mut LAST_TIMELINE_PLAYING: bool = false;
-static AUDIO_STATE_INIT: std::sync::Once = std::sync::Once::new();
+/// Playback state owned by one stream's callback
+struct CallbackState {
+    audio_state: AudioState,
+    last_timeline_playing: bool,
+    current_segment: Option<String>, // Segment the audio state is synchronized to
+    end_position: Option<f64>,       // Where the last buffer left the playhead
+    effects: EffectsChain,
+}
+
+impl CallbackState {
+    /// Build the playback state off the audio thread, with everything the engine has
+    /// handed over so far. That includes changes still on their way, so those are dropped.
+    fn new(shared: &SharedState, sample_rate: f32) -> Self {
+        let effects = {
+            let mut handoff = shared.master_effects.lock().unwrap();
+            handoff.clear();
+            EffectsChain::prepared(handoff.plan.rebuild(sample_rate), sample_rate)
+        };
+
+        // Timing follows the segment being played, once there is one
+        let mut audio_state = AudioState::new(sample_rate, 120.0);
+        {
+            let mut handoff = shared.mixer.lock().unwrap();
+            handoff.clear();
+            audio_state.apply_mixer(&mut handoff.plan.rebuild(sample_rate));
+        }
+
+        CallbackState {
+            audio_state,
+            last_timeline_playing: false,
+            current_segment: None,
+            end_position: None,
+            effects,
+        }
+    }
+}
+
+/// Playhead positions this close to a boundary count as on it, so rounding in the
+/// accumulated position can't leave a stray sample on the wrong side
+const BOUNDARY_EPSILON: f64 = 1e-9;
 
 fn audio_callback(
     data: &mut [f32],
-    sample_bank: &Arc<Mutex<SampleBank>>,
-    timeline: &Arc<Mutex<Timeline>>,
-    master_volume: &Arc<Mutex<f32>>,
+    state: &mut CallbackState,
+    shared: &SharedState,
     sample_rate: f32,
 ) {
     // Clear output buffer first
@@ -760,80 +1204,131 @@ fn audio_callback(
         *sample = 0.0;
     }
 
-    // Calculate time delta for this audio buffer
-    // With forced mono output, data.len() directly represents the number of samples/frames
-    let frames_per_buffer = data.len();
-    let delta_time = frames_per_buffer as f64 / sample_rate as f64;
-
     // Process timeline audio
-    let mut timeline_lock = timeline.lock().unwrap();
+    let mut timeline_lock = shared.timeline.lock().unwrap();
     let timeline_playing = timeline_lock.is_playing();
 
-    unsafe {
-        // Detect timeline state transitions
-        if LAST_TIMELINE_PLAYING && !timeline_playing {
-            // Timeline stopped - reset audio state
-            if let Some(ref mut audio_state) = AUDIO_STATE {
-                audio_state.reset();
-            }
-        } else if !LAST_TIMELINE_PLAYING && timeline_playing {
-            // Timeline started playing - synchronize audio state with timeline position
-            if let Some(segment) = timeline_lock.get_current_segment() {
-                if let Some(ref mut audio_state) = AUDIO_STATE {
-                    audio_state.synchronize_with_timeline(
-                        timeline_lock.current_position,
-                        segment.start_time,
-                        segment.bpm,
-                        sample_rate,
-                    );
-                }
-            }
-        }
-        LAST_TIMELINE_PLAYING = timeline_playing;
+    // A change being prepared right now is picked up with the next buffer
+    if let Ok(mut handoff) = shared.mixer.try_lock() {
+        handoff.receive(|update| state.audio_state.apply_mixer(update));
     }
 
-    if timeline_playing {
-        if timeline_lock.advance_position(delta_time) {
-            // Timeline is still playing, get current segment patterns
-            if let Some(segment) = timeline_lock.get_current_segment() {
-                let bank = sample_bank.lock().unwrap();
+    // Detect timeline state transitions
+    if state.last_timeline_playing && !timeline_playing {
+        // Timeline stopped - reset audio state
+        state.audio_state.reset();
+    }
+    if !state.last_timeline_playing && timeline_playing {
+        // Playback starts from silent effects, so it renders exactly like `render_offline`
+        state.audio_state.reset_effects();
+        state.effects.reset();
+    }
+    if state.last_timeline_playing != timeline_playing {
+        // Playback started, stopped, or a new stream took over mid-playback -
+        // synchronize with the timeline position on the next rendered sample
+        state.current_segment = None;
+    }
+    state.last_timeline_playing = timeline_playing;
 
-                // Initialize audio state if needed
-                unsafe {
-                    AUDIO_STATE_INIT.call_once(|| {
-                        AUDIO_STATE = Some(AudioState::new(sample_rate, segment.bpm));
-                    });
-
-                    if let Some(ref mut audio_state) = AUDIO_STATE {
-                        // Process audio directly from timeline patterns
-                        audio_state.process_patterns(
-                            data,
-                            &bank,
-                            &segment.patterns,
+    if timeline_playing {
+        let bank = shared.sample_bank.lock().unwrap();
+
+        // Render the buffer in chunks that end where the playing segment changes,
+        // so transitions land on the exact sample rather than the next buffer
+        // With forced mono output, data.len() directly represents the number of samples/frames
+        let mut offset = 0;
+        // The playhead moved since the last buffer, e.g. the user seeked
+        let mut jumped = state
+            .end_position
+            .is_some_and(|end| (end - timeline_lock.current_position).abs() > BOUNDARY_EPSILON);
+        while offset < data.len() {
+            let position = timeline_lock.current_position;
+            let lookup = position + BOUNDARY_EPSILON;
+
+            // First sample at or past the next boundary starts the next chunk
+            let until_boundary =
+                (timeline_lock.next_boundary(lookup) - lookup) * sample_rate as f64;
+            let frames = (until_boundary.ceil().max(1.0) as usize).min(data.len() - offset);
+            let chunk = &mut data[offset..offset + frames];
+
+            match timeline_lock.segment_at(lookup) {
+                Some(segment) => {
+                    let audio_state = &mut state.audio_state;
+
+                    if jumped || state.current_segment.as_deref() != Some(segment.id.as_str()) {
+                        audio_state.synchronize_with_timeline(
+                            lookup,
+                            segment.start_time,
                             segment.bpm,
                             sample_rate,
                         );
+                        state.current_segment = Some(segment.id.clone());
                     }
+
+                    // Process audio directly from timeline patterns
+                    audio_state.process_patterns(
+                        chunk,
+                        &bank,
+                        &segment.patterns,
+                        segment.bpm,
+                        sample_rate,
+                    );
                 }
+                None => {
+                    // Gap between segments - nothing triggers, but ringing voices carry on
+                    state.current_segment = None;
+                    state.audio_state.process_voices(chunk, &bank);
+                }
+            }
+
+            if !timeline_lock.advance_position(frames as f64 / sample_rate as f64) {
+                // Timeline finished; its voices end here, but track effects ring on
+                state
+                    .audio_state
+                    .process_effect_tails(&mut data[offset + frames..]);
+                break;
             }
+            // Playhead jumped backwards, i.e. wrapped around the loop region
+            jumped = timeline_lock.current_position < position;
+            offset += frames;
         }
-        // If timeline finished (advance_position returned false), buffer remains cleared
+    } else {
+        // Stopped voices are silent, but track effects and sends ring out
+        state.audio_state.process_effect_tails(data);
     }
-    // If timeline not playing, buffer remains cleared
+    state.end_position = timeline_playing.then_some(timeline_lock.current_position);
 
     // Apply master volume to the final output
-    let volume = *master_volume.lock().unwrap();
+    let volume = *shared.master_volume.lock().unwrap();
     if volume != 1.0 {
         for sample in data.iter_mut() {
             *sample *= volume;
         }
     }
+
+    // Master effects run even when stopped, so reverb tails ring out. A change being
+    // prepared right now is picked up with the next buffer.
+    if let Ok(mut handoff) = shared.master_effects.try_lock() {
+        handoff.receive(|update| state.effects.apply(update));
+    }
+    state.effects.process(data);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Engine state for driving `audio_callback` directly, at full volume with no effects
+    fn shared_state(sample_bank: &Arc<Mutex<SampleBank>>, timeline: Timeline) -> SharedState {
+        SharedState {
+            sample_bank: Arc::clone(sample_bank),
+            timeline: Arc::new(Mutex::new(timeline)),
+            master_volume: Arc::new(Mutex::new(1.0)),
+            master_effects: Arc::new(Mutex::new(Handoff::new(EffectsPlan::default()))),
+            mixer: Arc::new(Mutex::new(Handoff::new(MixerPlan::default()))),
+        }
+    }
+
     #[test]
     fn test_timeline_audio_direct_processing() {
         let mut sample_bank = SampleBank::new();
@@ -901,7 +1396,7 @@ mod tests {
 
         // Simulate some audio processing state
         audio_state.current_step = 8;
-        audio_state.sample_counter = 500;
+        audio_state.step_phase = 500.0;
 
         // Trigger a voice to be active
         audio_state.voices[0].trigger("kick".to_string(), 0.8);
@@ -909,7 +1404,7 @@ mod tests {
 
         // Verify state is dirty
         assert_eq!(audio_state.current_step, 8);
-        assert_eq!(audio_state.sample_counter, 500);
+        assert_eq!(audio_state.step_phase, 500.0);
         assert!(audio_state.voices[0].active);
         assert!(audio_state.voices[2].active);
         assert_eq!(audio_state.voices[0].sample_name, "kick");
@@ -919,7 +1414,7 @@ mod tests {
 
         // Verify all state is cleared
         assert_eq!(audio_state.current_step, 0);
-        assert_eq!(audio_state.sample_counter, 0);
+        assert_eq!(audio_state.step_phase, 0.0);
         assert!(!audio_state.voices[0].active);
         assert!(!audio_state.voices[2].active);
         assert!(audio_state.voices[0].sample_name.is_empty());
@@ -974,11 +1469,11 @@ mod tests {
         // Test synchronization at different timeline positions
         // At 120 BPM: 120 beats/min = 2 beats/sec = 8 sixteenth-notes/sec
         let test_cases = [
-            (0.0, 0), // Start of timeline
-            (0.5, 4), // 0.5 seconds = 4 steps at 120 BPM
-            (1.0, 8), // 1.0 seconds = 8 steps
-            (2.0, 0), // 2.0 seconds = 16 steps = 0 (looped)
-            (4.0, 0), // 4.0 seconds = 32 steps = 0 (looped)
+            (0.0, 0),  // Start of timeline
+            (0.5, 4),  // 0.5 seconds = 4 steps at 120 BPM
+            (1.0, 8),  // 1.0 seconds = 8 steps
+            (2.0, 16), // 2.0 seconds = 16 steps; each pattern wraps at its own length
+            (4.0, 32), // 4.0 seconds = 32 steps
         ];
 
         for (timeline_pos, expected_step) in test_cases {
@@ -989,8 +1484,8 @@ mod tests {
                 timeline_pos, expected_step, audio_state.current_step
             );
             assert_eq!(
-                audio_state.sample_counter, 0,
-                "Sample counter should be reset to 0 after synchronization"
+                audio_state.step_phase, 0.0,
+                "Step phase should be 0 when synchronized to the start of a step"
             );
         }
 
@@ -1005,6 +1500,215 @@ mod tests {
         println!("✅ Audio state timeline synchronization test passed");
     }
 
+    #[test]
+    fn test_patterns_loop_over_their_own_length() {
+        use crate::audio::samples::Sample;
+        use crate::audio::sequencer::Pattern;
+
+        let mut bank = SampleBank::new();
+        bank.add_sample("click".to_string(), Sample::from_data(vec![1.0], 44100, 1));
+        let mut triplets = Pattern::new("Triplets".to_string(), "click".to_string(), 12);
+        triplets.toggle_step(0);
+        let mut long = Pattern::new("Long".to_string(), "click".to_string(), 32);
+        long.toggle_step(20);
+        let patterns = vec![triplets, long];
+
+        // At 120 BPM each step lasts an eighth of a second
+        let mut audio_state = AudioState::new(44100.0, 120.0);
+        let mut triggered = Vec::new();
+        for step in 0..40 {
+            audio_state.synchronize_with_timeline(step as f64 / 8.0, 0.0, 120.0, 44100.0);
+            audio_state.trigger_current_step(&bank, &patterns);
+            if audio_state.voices.iter().any(|voice| voice.active) {
+                triggered.push(step);
+            }
+            audio_state.reset();
+        }
+        assert_eq!(triggered, vec![0, 12, 20, 24, 36]);
+
+        println!("✅ Pattern length loop test passed");
+    }
+
+    #[test]
+    fn test_new_stream_resumes_at_timeline_position() {
+        // A rebuilt stream starts with fresh callback state while the timeline keeps playing
+        let mut bank = SampleBank::new();
+        bank.load_default_samples();
+        let sample_bank = Arc::new(Mutex::new(bank));
+
+        let mut timeline = Timeline::new();
+        timeline.add_segment(crate::timeline::TimelineSegment::new(
+            "Test Segment".to_string(),
+            vec![crate::audio::sequencer::Pattern::new(
+                "Test".to_string(),
+                "kick".to_string(),
+                16,
+            )],
+            0.0,
+            4,
+            crate::audio::TimeSignature::four_four(),
+            120.0,
+        ));
+        timeline.play();
+        timeline.seek(1.0);
+        let shared = shared_state(&sample_bank, timeline);
+
+        let mut state = CallbackState::new(&shared, 48000.0);
+        let mut buffer = vec![0.0f32; 512];
+        audio_callback(&mut buffer, &mut state, &shared, 48000.0);
+
+        // 1 second at 120 BPM is step 8, and the playhead keeps moving from there
+        assert_eq!(state.audio_state.current_step, 8);
+        assert!(state.last_timeline_playing);
+        let position = shared.timeline.lock().unwrap().current_position;
+        assert!((position - (1.0 + 512.0 / 48000.0)).abs() < 1e-9);
+
+        println!("✅ New stream resumes at timeline position test passed");
+    }
+
+    #[test]
+    fn test_segment_transitions_are_sample_accurate() {
+        let sample_rate = 48000.0;
+        let mut bank = SampleBank::new();
+        bank.add_sample(
+            "click".to_string(),
+            crate::audio::samples::Sample::from_data(vec![1.0], 48000, 1),
+        );
+        let sample_bank = Arc::new(Mutex::new(bank));
+
+        // One click on the downbeat of each segment, told apart by velocity
+        let segment = |start_time: f64, bpm: f32, velocity: f32| {
+            let mut pattern =
+                crate::audio::sequencer::Pattern::new("Click".to_string(), "click".to_string(), 16);
+            pattern.steps[0].active = true;
+            pattern.steps[0].velocity = velocity;
+            crate::timeline::TimelineSegment::new(
+                format!("{} BPM", bpm),
+                vec![pattern],
+                start_time,
+                1,
+                crate::audio::TimeSignature::four_four(),
+                bpm,
+            )
+        };
+
+        // 1 bar at 128 BPM ends at sample 90000, mid-buffer at every size; a tempo
+        // change follows straight away, then a gap before the last segment at 5 s
+        let first = segment(0.0, 128.0, 0.25);
+        let second = segment(first.end_time(), 120.0, 0.5);
+        let third = segment(5.0, 120.0, 1.0);
+        let expected: [(usize, f32); 3] = [(0, 0.25), (90000, 0.5), (240000, 1.0)];
+
+        for buffer_size in [64, 256, 441, 4096] {
+            let mut timeline = Timeline::new();
+            timeline.add_segment(first.clone());
+            timeline.add_segment(second.clone());
+            timeline.add_segment(third.clone());
+            timeline.play();
+            let shared = shared_state(&sample_bank, timeline);
+
+            let mut state = CallbackState::new(&shared, sample_rate);
+            let mut buffer = vec![0.0f32; buffer_size];
+            let mut hits = Vec::new();
+            let mut rendered = 0;
+            while rendered < 245000 {
+                audio_callback(&mut buffer, &mut state, &shared, sample_rate);
+                for (i, sample) in buffer.iter().enumerate() {
+                    if *sample != 0.0 {
+                        hits.push((rendered + i, *sample));
+                    }
+                }
+                rendered += buffer_size;
+            }
+
+            assert_eq!(
+                hits, expected,
+                "Segment downbeats should land on the exact sample with {}-sample buffers",
+                buffer_size
+            );
+        }
+
+        println!("✅ Sample-accurate segment transitions test passed");
+    }
+
+    #[test]
+    fn test_playback_with_effects_matches_an_offline_render() {
+        let sample_rate = 48000.0;
+        let mut bank = SampleBank::new();
+        bank.load_default_samples();
+        let sample_bank = Arc::new(Mutex::new(bank));
+
+        let mut kick =
+            crate::audio::sequencer::Pattern::new("Kick".to_string(), "kick".to_string(), 16);
+        let mut snare =
+            crate::audio::sequencer::Pattern::new("Snare".to_string(), "snare".to_string(), 16);
+        for step in [0, 8] {
+            kick.steps[step].active = true;
+        }
+        for step in [4, 12] {
+            snare.steps[step].active = true;
+        }
+        let mut timeline = Timeline::new();
+        timeline.add_segment(crate::timeline::TimelineSegment::new(
+            "Beat".to_string(),
+            vec![kick, snare],
+            0.0,
+            1,
+            crate::audio::TimeSignature::four_four(),
+            120.0,
+        ));
+        timeline.play();
+        let shared = shared_state(&sample_bank, timeline);
+
+        let mut snare_effects = crate::audio::mixer::TrackEffects::default();
+        snare_effects.filter.enabled = true;
+        snare_effects.transient.enabled = true;
+        snare_effects.transient.attack = 0.5;
+        snare_effects.reverb_send = 0.4;
+        snare_effects.delay_send = 0.3;
+        // Short tails keep the export short
+        let mut mixer = MixerSettings::default();
+        mixer.tracks.insert("snare".to_string(), snare_effects);
+        mixer.reverb_bus.time = 0.3;
+        mixer.delay_bus.feedback = 0.1;
+        shared.set_mixer(&mixer, sample_rate);
+        let mut effects = MasterEffects::default();
+        effects.reverb.enabled = true;
+        effects.reverb.time = 0.3;
+        shared.set_master_effects(&effects, sample_rate);
+
+        let render = |state: &mut CallbackState, buffer_size: usize, len: usize| {
+            let mut output = Vec::new();
+            let mut buffer = vec![0.0f32; buffer_size];
+            while output.len() < len {
+                audio_callback(&mut buffer, state, &shared, sample_rate);
+                output.extend_from_slice(&buffer);
+            }
+            output.truncate(len);
+            output
+        };
+
+        // An export renders once from fresh state, tails and all
+        let timeline = shared.timeline.lock().unwrap().clone();
+        let export = render_offline(&shared, timeline, sample_rate);
+        assert!(export.len() > 2 * 48000);
+
+        // Live playback that already played, stopped and let the tails ring
+        shared.timeline.lock().unwrap().stop();
+        shared.timeline.lock().unwrap().play();
+        let mut state = CallbackState::new(&shared, sample_rate);
+        render(&mut state, 256, 24000);
+        shared.timeline.lock().unwrap().stop();
+        let tails = render(&mut state, 256, 4800);
+        assert!(tails.iter().any(|x| *x != 0.0));
+
+        // Through the end of the timeline, where voices stop mid-buffer, and the tails
+        shared.timeline.lock().unwrap().play();
+        assert_eq!(render(&mut state, 256, export.len()), export);
+
+        println!("✅ Offline render matches playback test passed");
+    }
+
     #[test]
     fn test_timeline_stop_start_cycles() {
         let mut timeline = Timeline::new();
@@ -1406,6 +2110,138 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_apply_settings_while_running() {
+        match AudioEngine::new() {
+            Ok(mut engine) => {
+                // Volume changes apply without touching the stream
+                let mut settings = engine.settings().clone();
+                settings.master_volume = 0.5;
+                assert!(engine.apply_settings(settings.clone()).is_ok());
+                assert_eq!(engine.get_master_volume(), 0.5);
+
+                // A device that does not exist is rejected and the running config kept
+                settings.preferred_device = Some("Nonexistent Device 123".to_string());
+                assert!(engine.apply_settings(settings).is_err());
+                assert_eq!(engine.get_current_device_name(), "Default Device");
+                assert_eq!(engine.settings().preferred_device, None);
+
+                println!("✅ Apply settings while running test passed");
+            }
+            Err(e) => {
+                println!("⚠️  Apply settings test skipped (no audio hardware): {}", e);
+            }
+        }
+    }
+
+    #[test]
+    fn test_null_and_file_backends_play_without_device() {
+        let dir = tempfile::tempdir().unwrap();
+        let wav_path = dir.path().join("live.wav");
+
+        for backend in [AudioBackend::Null, AudioBackend::File(wav_path.clone())] {
+            let settings = AudioSettings {
+                backend: backend.clone(),
+                buffer_size: 256,
+                ..AudioSettings::default()
+            };
+            let mut engine = AudioEngine::new_with_settings(settings).unwrap();
+            assert_eq!(engine.get_current_device_name(), backend.display_name());
+            assert!(engine.monitor_device_availability().unwrap());
+            assert!((engine.output_latency() - 256.0 / 44100.0).abs() < 1e-9);
+
+            {
+                let timeline = engine.timeline();
+                let mut timeline = timeline.lock().unwrap();
+                let mut pattern = crate::audio::sequencer::Pattern::new(
+                    "Kick".to_string(),
+                    "kick".to_string(),
+                    16,
+                );
+                pattern.steps[0].active = true;
+                timeline.add_segment(crate::timeline::TimelineSegment::new(
+                    "Test Segment".to_string(),
+                    vec![pattern],
+                    0.0,
+                    4,
+                    crate::audio::TimeSignature::four_four(),
+                    120.0,
+                ));
+                timeline.play();
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            assert!(engine.timeline().lock().unwrap().current_position > 0.0);
+
+            // Reconfiguring keeps the timeline where it is
+            engine.reconfigure(48000, 512).unwrap();
+            assert_eq!(engine.sample_rate(), 48000.0);
+            assert!((engine.output_latency() - 512.0 / 48000.0).abs() < 1e-9);
+            assert!(engine.timeline().lock().unwrap().current_position > 0.0);
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+
+        // Reconfiguring started the file over at the new sample rate
+        let reader = hound::WavReader::open(&wav_path).unwrap();
+        assert_eq!(reader.spec().sample_rate, 48000);
+        assert!(reader.len() > 0);
+
+        println!("✅ Null and file backends test passed");
+    }
+
+    #[test]
+    fn test_export_wav_writes_the_offline_render() {
+        let dir = tempfile::tempdir().unwrap();
+        let wav_path = dir.path().join("export.wav");
+        let settings = AudioSettings {
+            backend: AudioBackend::Null,
+            ..AudioSettings::default()
+        };
+        let engine = AudioEngine::new_with_settings(settings).unwrap();
+
+        // Nothing to render yet
+        assert!(engine.export_wav(&wav_path).is_err());
+
+        let mut pattern =
+            crate::audio::sequencer::Pattern::new("Snare".to_string(), "snare".to_string(), 16);
+        pattern.steps[4].active = true;
+        engine
+            .timeline()
+            .lock()
+            .unwrap()
+            .add_segment(crate::timeline::TimelineSegment::new(
+                "Test Segment".to_string(),
+                vec![pattern],
+                0.0,
+                1,
+                crate::audio::TimeSignature::four_four(),
+                120.0,
+            ));
+        let mut mixer = MixerSettings::default();
+        mixer.tracks.insert(
+            "snare".to_string(),
+            crate::audio::mixer::TrackEffects {
+                delay_send: 0.5,
+                ..Default::default()
+            },
+        );
+        engine.set_mixer(mixer);
+        engine.export_wav(&wav_path).unwrap();
+
+        // The live timeline is left alone
+        assert!(!engine.timeline().lock().unwrap().is_playing());
+
+        let mut reader = hound::WavReader::open(&wav_path).unwrap();
+        assert_eq!(reader.spec().sample_rate, 44100);
+        let samples: Vec<f32> = reader.samples::<f32>().map(|s| s.unwrap()).collect();
+        let timeline = engine.timeline().lock().unwrap().clone();
+        assert_eq!(samples, render_offline(&engine.shared, timeline, 44100.0));
+        // The bar lasts 2 seconds, and the delay rings on past it
+        assert!(samples.len() > 2 * 44100);
+        assert!(samples[2 * 44100..].iter().any(|x| x.abs() > 1e-4));
+
+        println!("✅ WAV export test passed");
+    }
+
     #[test]
     fn test_device_fallback_logic() {
         // Test device fallback decision logic without requiring actual audio hardware
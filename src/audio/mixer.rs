@@ -0,0 +1,915 @@
+//! Per-track insert effects and the shared send buses. A track is everything that
+//! plays one sample, so the snare can be shaped apart from the kick. Tracks are
+//! mixed together with the bus returns before the master volume and master effects.
+
+use fundsp::prelude::{
+    amp_db, bandpass, db_amp, highpass, lowpass, pass, shared, var, AudioUnit, Shared,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::effects::{new_reverb, smoothing_coeff, ReverbRoom};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FilterMode {
+    #[default]
+    Lowpass,
+    Highpass,
+    Bandpass,
+}
+
+impl FilterMode {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            FilterMode::Lowpass => "Low-pass",
+            FilterMode::Highpass => "High-pass",
+            FilterMode::Bandpass => "Band-pass",
+        }
+    }
+}
+
+/// Resonant state variable filter
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterSettings {
+    pub enabled: bool,
+    pub mode: FilterMode,
+    pub cutoff: f32, // Hz
+    pub q: f32,
+}
+
+impl Default for FilterSettings {
+    fn default() -> Self {
+        FilterSettings {
+            enabled: false,
+            mode: FilterMode::Lowpass,
+            cutoff: 2000.0,
+            q: 0.707,
+        }
+    }
+}
+
+/// Soft tanh clipping
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SaturationSettings {
+    pub enabled: bool,
+    pub drive_db: f32, // Gain into the clipper
+    pub mix: f32,      // Saturated share of the output, 0 to 1
+}
+
+impl Default for SaturationSettings {
+    fn default() -> Self {
+        SaturationSettings {
+            enabled: false,
+            drive_db: 6.0,
+            mix: 1.0,
+        }
+    }
+}
+
+/// Envelope based transient shaper
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransientSettings {
+    pub enabled: bool,
+    pub attack: f32,  // -1 softens the hit, 1 sharpens it
+    pub sustain: f32, // -1 tightens the decay, 1 lengthens it
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BitcrusherSettings {
+    pub enabled: bool,
+    pub bits: u32,       // 1 to 16
+    pub downsample: u32, // Hold each sample this many samples, at least 1
+}
+
+impl Default for BitcrusherSettings {
+    fn default() -> Self {
+        BitcrusherSettings {
+            enabled: false,
+            bits: 8,
+            downsample: 1,
+        }
+    }
+}
+
+/// Insert effects, applied in field order, and post-insert send levels of one track
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrackEffects {
+    pub filter: FilterSettings,
+    pub saturation: SaturationSettings,
+    pub transient: TransientSettings,
+    pub bitcrusher: BitcrusherSettings,
+    pub reverb_send: f32, // 0 to 1
+    pub delay_send: f32,  // 0 to 1
+}
+
+impl TrackEffects {
+    /// True when the track plays straight into the mix
+    pub fn is_bypassed(&self) -> bool {
+        !(self.filter.enabled
+            || self.saturation.enabled
+            || self.transient.enabled
+            || self.bitcrusher.enabled
+            || self.reverb_send > 0.0
+            || self.delay_send > 0.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReverbBus {
+    pub room_size: f32, // Meters
+    pub time: f32,      // Seconds to decay by 60 dB
+    pub damping: f32,   // High frequency damping, 0 to 1
+    pub level: f32,     // Return level, 0 to 1
+}
+
+impl Default for ReverbBus {
+    fn default() -> Self {
+        ReverbBus {
+            room_size: 20.0,
+            time: 2.0,
+            damping: 0.5,
+            level: 1.0,
+        }
+    }
+}
+
+impl ReverbBus {
+    fn room(&self) -> ReverbRoom {
+        ReverbRoom {
+            room_size: self.room_size,
+            time: self.time,
+            damping: self.damping,
+        }
+    }
+}
+
+/// Feedback delay
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DelayBus {
+    pub time_ms: f32,  // Up to MAX_DELAY_SECONDS
+    pub feedback: f32, // Share of each echo fed back, 0 to 0.95
+    pub level: f32,    // Return level, 0 to 1
+}
+
+impl Default for DelayBus {
+    fn default() -> Self {
+        DelayBus {
+            time_ms: 375.0,
+            feedback: 0.35,
+            level: 1.0,
+        }
+    }
+}
+
+/// Track effects and send buses, saved with the project. Tracks without an entry
+/// play dry, which leaves the audio untouched.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MixerSettings {
+    pub tracks: BTreeMap<String, TrackEffects>, // By sample name
+    pub reverb_bus: ReverbBus,
+    pub delay_bus: DelayBus,
+}
+
+impl MixerSettings {
+    /// Effects of the track playing `sample_name`
+    pub fn track(&self, sample_name: &str) -> TrackEffects {
+        self.tracks.get(sample_name).copied().unwrap_or_default()
+    }
+
+    /// Tracks that play through the mixer rather than straight out
+    fn active_tracks(&self) -> impl Iterator<Item = (&String, &TrackEffects)> {
+        self.tracks
+            .iter()
+            .filter(|(_, effects)| !effects.is_bypassed())
+    }
+
+    fn is_active(&self, sample_name: &str) -> bool {
+        self.tracks
+            .get(sample_name)
+            .is_some_and(|effects| !effects.is_bypassed())
+    }
+}
+
+// Longest delay the delay bus can hold
+const MAX_DELAY_SECONDS: f32 = 2.0;
+// Samples each mix buffer holds up front, the largest buffer size the settings offer
+const PREALLOCATED_SAMPLES: usize = 4096;
+// Transient shaper envelope times
+const TRANSIENT_FAST_MS: f32 = 1.0;
+const TRANSIENT_SLOW_MS: f32 = 20.0;
+const TRANSIENT_RELEASE_MS: f32 = 50.0;
+const TRANSIENT_TAIL_RELEASE_MS: f32 = 300.0;
+// Most the transient shaper boosts or cuts
+const TRANSIENT_MAX_DB: f32 = 24.0;
+
+/// Mixes the tracks of one output through their inserts and the send buses.
+///
+/// Each chunk goes `begin`, then voices write into `track_buffer` (or straight into
+/// the output when their track has no effects), then `finish`. All state advances
+/// one sample at a time, so the result doesn't depend on how audio is chunked.
+/// Changes arrive as a `MixerUpdate` with everything they need already built.
+pub struct Mixer {
+    reverb_bus: ReverbBus,
+    delay_bus: DelayBus,
+    sample_rate: f32,
+    channels: Vec<TrackChannel>, // Tracks that aren't bypassed
+    len: usize,                  // Samples in the chunk being mixed
+    reverb: Option<Box<dyn AudioUnit>>,
+    reverb_input: Vec<f32>,
+    delay: Option<DelayLine>,
+    delay_input: Vec<f32>,
+}
+
+impl Mixer {
+    pub fn new(sample_rate: f32) -> Self {
+        Mixer {
+            reverb_bus: ReverbBus::default(),
+            delay_bus: DelayBus::default(),
+            sample_rate,
+            channels: Vec::new(),
+            len: 0,
+            reverb: None,
+            reverb_input: vec![0.0; PREALLOCATED_SAMPLES],
+            delay: None,
+            delay_input: vec![0.0; PREALLOCATED_SAMPLES],
+        }
+    }
+
+    /// Switch to a prepared change without allocating. Tracks that stay in use keep
+    /// their state. `update` is left holding what it replaced, to be freed away from
+    /// the audio thread.
+    pub fn apply(&mut self, update: &mut MixerUpdate) {
+        self.reverb_bus = update.settings.reverb_bus;
+        self.delay_bus = update.settings.delay_bus;
+
+        // `update.channels` has room for every track, so this never grows it
+        for (name, effects) in update.settings.active_tracks() {
+            let channel = match self.channels.iter().position(|c| &c.name == name) {
+                Some(index) => Some(self.channels.swap_remove(index)),
+                None => match update.fresh.iter().position(|c| &c.name == name) {
+                    Some(index) => Some(update.fresh.swap_remove(index)),
+                    None => None, // Not prepared for this mixer, so the track plays dry
+                },
+            };
+            if let Some(mut channel) = channel {
+                channel.configure(effects);
+                update.channels.push(channel);
+            }
+        }
+        std::mem::swap(&mut self.channels, &mut update.channels);
+
+        if update.reverb.is_some() {
+            std::mem::swap(&mut self.reverb, &mut update.reverb);
+        }
+        if update.delay.is_some() {
+            std::mem::swap(&mut self.delay, &mut update.delay);
+        }
+    }
+
+    /// True when nothing would change the audio, so voices can play straight to the output
+    pub fn is_idle(&self) -> bool {
+        self.channels.is_empty() && self.reverb.is_none() && self.delay.is_none()
+    }
+
+    /// Clear filter memory, envelopes and bus tails, as if the mixer was just built
+    pub fn reset(&mut self) {
+        for channel in &mut self.channels {
+            channel.reset();
+        }
+        if let Some(reverb) = self.reverb.as_mut() {
+            reverb.reset();
+        }
+        if let Some(delay) = self.delay.as_mut() {
+            delay.reset();
+        }
+    }
+
+    /// Start mixing a chunk of `len` samples with empty track buffers
+    pub fn begin(&mut self, len: usize) {
+        self.len = len;
+        for channel in &mut self.channels {
+            clear(&mut channel.buffer, len);
+        }
+        clear(&mut self.reverb_input, len);
+        clear(&mut self.delay_input, len);
+    }
+
+    /// Buffer collecting the chunk of the track playing `sample_name`, or None when
+    /// the track is bypassed and plays straight into the output
+    pub fn track_buffer(&mut self, sample_name: &str) -> Option<&mut [f32]> {
+        let len = self.len;
+        self.channels
+            .iter_mut()
+            .find(|channel| channel.name == sample_name)
+            .map(|channel| &mut channel.buffer[..len])
+    }
+
+    /// Run the track inserts and the buses over the chunk and add them to `output`
+    pub fn finish(&mut self, output: &mut [f32]) {
+        let len = self.len.min(output.len());
+
+        for channel in &mut self.channels {
+            let buffer = &mut channel.buffer[..len];
+            channel.inserts.process(buffer, &channel.effects);
+
+            let reverb_send = channel.effects.reverb_send.clamp(0.0, 1.0);
+            let delay_send = channel.effects.delay_send.clamp(0.0, 1.0);
+            for (i, x) in buffer.iter().enumerate() {
+                output[i] += x;
+                self.reverb_input[i] += x * reverb_send;
+                self.delay_input[i] += x * delay_send;
+            }
+        }
+
+        if let Some(reverb) = self.reverb.as_mut() {
+            let level = self.reverb_bus.level.clamp(0.0, 1.0);
+            for (out, x) in output[..len].iter_mut().zip(&self.reverb_input) {
+                let (left, right) = reverb.filter_stereo(*x, *x);
+                *out += (left + right) * 0.5 * level;
+            }
+        }
+
+        if let Some(delay) = self.delay.as_mut() {
+            let bus = self.delay_bus;
+            let delay_samples = (bus.time_ms.max(0.0) * 0.001 * self.sample_rate).round() as usize;
+            let feedback = bus.feedback.clamp(0.0, 0.95);
+            let level = bus.level.clamp(0.0, 1.0);
+            for (out, x) in output[..len].iter_mut().zip(&self.delay_input) {
+                *out += delay.process(*x, delay_samples, feedback) * level;
+            }
+        }
+    }
+}
+
+/// The mixer settings last handed to a mixer, and which buses it has built. Lives
+/// away from the audio thread, where it works out what each change has to build.
+#[derive(Debug, Clone, Default)]
+pub struct MixerPlan {
+    settings: MixerSettings,
+    reverb_room: Option<ReverbRoom>, // Room the reverb bus was built for
+    delay: bool,                     // Whether the delay bus was built
+}
+
+impl MixerPlan {
+    pub fn settings(&self) -> &MixerSettings {
+        &self.settings
+    }
+
+    /// Build what switching to `settings` needs and count it as handed over
+    pub fn prepare(&mut self, settings: &MixerSettings, sample_rate: f32) -> MixerUpdate {
+        let fresh = settings
+            .active_tracks()
+            .filter(|(name, _)| !self.settings.is_active(name))
+            .map(|(name, _)| TrackChannel::new(name.clone(), sample_rate))
+            .collect();
+
+        // The buses allocate their delay lines, so only build them once something is sent.
+        // After that they stay, so their tails ring out when the sends are turned down.
+        let room = settings.reverb_bus.room();
+        let rebuild = match self.reverb_room {
+            Some(built_for) => built_for != room,
+            None => settings
+                .active_tracks()
+                .any(|(_, effects)| effects.reverb_send > 0.0),
+        };
+        let reverb = rebuild.then(|| {
+            self.reverb_room = Some(room);
+            new_reverb(room, sample_rate)
+        });
+
+        let send_delay = settings
+            .active_tracks()
+            .any(|(_, effects)| effects.delay_send > 0.0);
+        let delay = (!self.delay && send_delay).then(|| {
+            self.delay = true;
+            DelayLine::new(sample_rate)
+        });
+
+        self.settings = settings.clone();
+        MixerUpdate {
+            settings: settings.clone(),
+            channels: Vec::with_capacity(settings.active_tracks().count()),
+            fresh,
+            reverb,
+            delay,
+        }
+    }
+
+    /// Everything handed over so far, built anew for a mixer starting from nothing
+    pub fn rebuild(&self, sample_rate: f32) -> MixerUpdate {
+        let mut update = MixerPlan::default().prepare(&self.settings, sample_rate);
+        if let (None, Some(room)) = (&update.reverb, self.reverb_room) {
+            update.reverb = Some(new_reverb(room, sample_rate));
+        }
+        if update.delay.is_none() && self.delay {
+            update.delay = Some(DelayLine::new(sample_rate));
+        }
+        update
+    }
+}
+
+/// A change of mixer settings with the channels and buses it allocates already built
+pub struct MixerUpdate {
+    settings: MixerSettings,
+    channels: Vec<TrackChannel>, // Empty, with room for every track the mixer will have
+    fresh: Vec<TrackChannel>,    // For tracks the mixer doesn't have yet
+    reverb: Option<Box<dyn AudioUnit>>, // Built for a new room, or None to keep the old one
+    delay: Option<DelayLine>,
+}
+
+impl MixerUpdate {
+    /// Take over what `unapplied`, an earlier change that never reached the mixer, built
+    pub fn absorb(&mut self, unapplied: MixerUpdate) {
+        self.fresh.extend(unapplied.fresh);
+        if self.reverb.is_none() {
+            self.reverb = unapplied.reverb;
+        }
+        if self.delay.is_none() {
+            self.delay = unapplied.delay;
+        }
+    }
+}
+
+/// Zero the first `len` samples of `buffer`, growing it if needed
+fn clear(buffer: &mut Vec<f32>, len: usize) {
+    if buffer.len() < len {
+        buffer.resize(len, 0.0);
+    }
+    buffer[..len].fill(0.0);
+}
+
+/// One track's buffer and inserts
+struct TrackChannel {
+    name: String, // Sample the track plays
+    effects: TrackEffects,
+    buffer: Vec<f32>,
+    inserts: Inserts,
+}
+
+impl TrackChannel {
+    fn new(name: String, sample_rate: f32) -> Self {
+        TrackChannel {
+            name,
+            effects: TrackEffects::default(),
+            buffer: vec![0.0; PREALLOCATED_SAMPLES],
+            inserts: Inserts::new(sample_rate),
+        }
+    }
+
+    fn configure(&mut self, effects: &TrackEffects) {
+        self.effects = *effects;
+        self.inserts.configure(effects);
+    }
+
+    fn reset(&mut self) {
+        self.inserts.reset();
+    }
+}
+
+/// The insert effects of a track. There is a filter for every mode, built up front so
+/// switching never allocates; they read their cutoff and Q from shared values, so
+/// those can change without resetting them.
+struct Inserts {
+    filters: [Box<dyn AudioUnit>; 3], // In `FilterMode` order
+    mode: FilterMode,
+    cutoff: Shared,
+    q: Shared,
+    sample_rate: f32,
+    transient: TransientShaper,
+    crusher: Bitcrusher,
+}
+
+impl Inserts {
+    fn new(sample_rate: f32) -> Self {
+        let cutoff = shared(2000.0);
+        let q = shared(0.707);
+        let filter = |mode: FilterMode| {
+            let params = pass() | var(&cutoff) | var(&q);
+            let mut unit: Box<dyn AudioUnit> = match mode {
+                FilterMode::Lowpass => Box::new(params >> lowpass::<f32>()),
+                FilterMode::Highpass => Box::new(params >> highpass::<f32>()),
+                FilterMode::Bandpass => Box::new(params >> bandpass::<f32>()),
+            };
+            unit.set_sample_rate(sample_rate as f64);
+            unit
+        };
+
+        Inserts {
+            filters: [
+                filter(FilterMode::Lowpass),
+                filter(FilterMode::Highpass),
+                filter(FilterMode::Bandpass),
+            ],
+            mode: FilterMode::default(),
+            cutoff,
+            q,
+            sample_rate,
+            transient: TransientShaper::new(sample_rate),
+            crusher: Bitcrusher::new(),
+        }
+    }
+
+    fn configure(&mut self, effects: &TrackEffects) {
+        let filter = effects.filter;
+        self.cutoff
+            .set_value(filter.cutoff.clamp(20.0, self.sample_rate * 0.45));
+        self.q.set_value(filter.q.max(0.1));
+
+        // Another mode starts from silence, like a newly built filter
+        if filter.mode != self.mode {
+            self.mode = filter.mode;
+            self.filters[self.mode as usize].reset();
+        }
+    }
+
+    fn reset(&mut self) {
+        for filter in &mut self.filters {
+            filter.reset();
+        }
+        self.transient.reset();
+        self.crusher.reset();
+    }
+
+    fn process(&mut self, buffer: &mut [f32], effects: &TrackEffects) {
+        let saturation = effects.saturation;
+        let drive = db_amp(saturation.drive_db);
+        let mix = saturation.mix.clamp(0.0, 1.0);
+        let transient = effects.transient;
+        let crusher = effects.bitcrusher;
+
+        for sample in buffer.iter_mut() {
+            let mut x = *sample;
+
+            if effects.filter.enabled {
+                x = self.filters[self.mode as usize].filter_mono(x);
+            }
+
+            if saturation.enabled {
+                x = x * (1.0 - mix) + (x * drive).tanh() * mix;
+            }
+
+            if transient.enabled {
+                x = self.transient.process(x, &transient);
+            }
+
+            if crusher.enabled {
+                x = self.crusher.process(x, &crusher);
+            }
+
+            *sample = x;
+        }
+    }
+}
+
+/// Compares a fast and a slow envelope to find the hit, and a long-release envelope
+/// against the fast one to find the decay, then boosts or cuts each
+struct TransientShaper {
+    fast_attack: f32,
+    slow_attack: f32,
+    release: f32,
+    tail_release: f32,
+    fast: f32,
+    slow: f32,
+    tail: f32,
+}
+
+impl TransientShaper {
+    fn new(sample_rate: f32) -> Self {
+        TransientShaper {
+            fast_attack: smoothing_coeff(TRANSIENT_FAST_MS, sample_rate),
+            slow_attack: smoothing_coeff(TRANSIENT_SLOW_MS, sample_rate),
+            release: smoothing_coeff(TRANSIENT_RELEASE_MS, sample_rate),
+            tail_release: smoothing_coeff(TRANSIENT_TAIL_RELEASE_MS, sample_rate),
+            fast: 0.0,
+            slow: 0.0,
+            tail: 0.0,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.fast = 0.0;
+        self.slow = 0.0;
+        self.tail = 0.0;
+    }
+
+    fn process(&mut self, x: f32, settings: &TransientSettings) -> f32 {
+        let level = x.abs();
+        self.fast = follow(self.fast, level, self.fast_attack, self.release);
+        self.slow = follow(self.slow, level, self.slow_attack, self.release);
+        self.tail = follow(self.tail, level, self.fast_attack, self.tail_release);
+
+        let fast_db = amp_db(self.fast.max(1e-6));
+        let attack_db = (fast_db - amp_db(self.slow.max(1e-6))).max(0.0);
+        let sustain_db = (amp_db(self.tail.max(1e-6)) - fast_db).max(0.0);
+
+        let gain_db = (settings.attack.clamp(-1.0, 1.0) * attack_db
+            + settings.sustain.clamp(-1.0, 1.0) * sustain_db)
+            .clamp(-TRANSIENT_MAX_DB, TRANSIENT_MAX_DB);
+        x * db_amp(gain_db)
+    }
+}
+
+/// One-pole envelope follower step with separate attack and release
+fn follow(envelope: f32, level: f32, attack_coeff: f32, release_coeff: f32) -> f32 {
+    let coeff = if level > envelope {
+        attack_coeff
+    } else {
+        release_coeff
+    };
+    level + coeff * (envelope - level)
+}
+
+/// Quantizes to fewer bits and holds samples to lower the sample rate
+struct Bitcrusher {
+    held: f32,
+    counter: u32, // Samples since `held` was taken
+}
+
+impl Bitcrusher {
+    fn new() -> Self {
+        Bitcrusher {
+            held: 0.0,
+            counter: 0,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.held = 0.0;
+        self.counter = 0;
+    }
+
+    fn process(&mut self, x: f32, settings: &BitcrusherSettings) -> f32 {
+        if self.counter == 0 {
+            let levels = (1u32 << (settings.bits.clamp(1, 16) - 1)) as f32;
+            self.held = (x * levels).round() / levels;
+        }
+        self.counter = (self.counter + 1) % settings.downsample.max(1);
+        self.held
+    }
+}
+
+/// Ring buffer feedback delay
+struct DelayLine {
+    buffer: Vec<f32>,
+    write: usize,
+}
+
+impl DelayLine {
+    fn new(sample_rate: f32) -> Self {
+        DelayLine {
+            buffer: vec![0.0; (MAX_DELAY_SECONDS * sample_rate) as usize + 1],
+            write: 0,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.buffer.fill(0.0);
+        self.write = 0;
+    }
+
+    fn process(&mut self, input: f32, delay_samples: usize, feedback: f32) -> f32 {
+        let len = self.buffer.len();
+        let delay_samples = delay_samples.clamp(1, len - 1);
+        let output = self.buffer[(self.write + len - delay_samples) % len];
+        self.buffer[self.write] = input + output * feedback;
+        self.write = (self.write + 1) % len;
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Mix `input` as the "snare" track in chunks of `chunk_size`
+    fn render(mixer: &mut Mixer, input: &[f32], chunk_size: usize) -> Vec<f32> {
+        let mut output = vec![0.0; input.len()];
+        for (out, chunk) in output.chunks_mut(chunk_size).zip(input.chunks(chunk_size)) {
+            mixer.begin(chunk.len());
+            match mixer.track_buffer("snare") {
+                Some(buffer) => buffer.copy_from_slice(chunk),
+                None => out.copy_from_slice(chunk),
+            }
+            mixer.finish(out);
+        }
+        output
+    }
+
+    fn hits(len: usize, every: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| {
+                let t = i % every;
+                (-(t as f32) / 400.0).exp() * ((i as f32) * 0.37).sin()
+            })
+            .collect()
+    }
+
+    /// Mixer built from nothing for `settings`, as an output opening does
+    fn mixer_for(settings: &MixerSettings) -> Mixer {
+        let mut mixer = Mixer::new(48000.0);
+        mixer.apply(&mut MixerPlan::default().prepare(settings, 48000.0));
+        mixer
+    }
+
+    fn snare_mixer(effects: TrackEffects) -> MixerSettings {
+        let mut settings = MixerSettings::default();
+        settings.tracks.insert("snare".to_string(), effects);
+        settings
+    }
+
+    #[test]
+    fn test_default_mixer_leaves_audio_untouched() {
+        let mut mixer = mixer_for(&MixerSettings::default());
+        assert!(mixer.is_idle());
+
+        let input = hits(4800, 1200);
+        assert_eq!(render(&mut mixer, &input, 256), input);
+
+        println!("✅ Bypassed mixer test passed");
+    }
+
+    #[test]
+    fn test_inserts_only_change_their_track() {
+        let mut effects = TrackEffects::default();
+        effects.bitcrusher.enabled = true;
+        effects.bitcrusher.bits = 2;
+
+        let mut mixer = mixer_for(&snare_mixer(effects));
+        assert!(mixer.track_buffer("kick").is_none());
+
+        let input = hits(4800, 1200);
+        let output = render(&mut mixer, &input, 256);
+        assert_ne!(output, input);
+        // 2 bits leave the levels -1, -0.5, 0, 0.5 and 1
+        assert!(output.iter().all(|x| (x * 2.0).fract() == 0.0));
+
+        println!("✅ Track insert test passed");
+    }
+
+    #[test]
+    fn test_each_insert_changes_the_sound() {
+        let input = hits(9600, 2400);
+        let variants: [fn(&mut TrackEffects); 4] = [
+            |fx| fx.filter.enabled = true,
+            |fx| fx.saturation.enabled = true,
+            |fx| {
+                fx.transient.enabled = true;
+                fx.transient.attack = 1.0;
+            },
+            |fx| fx.bitcrusher.enabled = true,
+        ];
+
+        for enable in variants {
+            let mut effects = TrackEffects::default();
+            enable(&mut effects);
+            let mut mixer = mixer_for(&snare_mixer(effects));
+            assert_ne!(render(&mut mixer, &input, 512), input, "{:?}", effects);
+        }
+
+        println!("✅ Insert effects test passed");
+    }
+
+    #[test]
+    fn test_sends_ring_on_after_the_track() {
+        let effects = TrackEffects {
+            reverb_send: 0.5,
+            delay_send: 0.5,
+            ..TrackEffects::default()
+        };
+
+        let mut mixer = mixer_for(&snare_mixer(effects));
+
+        let mut input = vec![0.0; 48000];
+        input[..2400].copy_from_slice(&hits(2400, 2400));
+        let output = render(&mut mixer, &input, 512);
+
+        // The dry track passes through unchanged until the buses answer
+        assert_eq!(output[..100], input[..100]);
+        // Long after the hit, the reverb and delay are still sounding
+        assert!(output[24000..].iter().any(|x| x.abs() > 1e-4));
+
+        println!("✅ Send bus tail test passed");
+    }
+
+    #[test]
+    fn test_delay_bus_echoes_on_time() {
+        let mut settings = snare_mixer(TrackEffects {
+            delay_send: 1.0,
+            ..TrackEffects::default()
+        });
+        settings.delay_bus = DelayBus {
+            time_ms: 10.0,
+            feedback: 0.5,
+            level: 1.0,
+        };
+
+        let mut mixer = mixer_for(&settings);
+        let mut input = vec![0.0; 1500];
+        input[0] = 1.0;
+        let output = render(&mut mixer, &input, 300);
+
+        assert_eq!(output[0], 1.0);
+        assert_eq!(output[480], 1.0);
+        assert_eq!(output[960], 0.5);
+        assert_eq!(output[1440], 0.25);
+        assert_eq!(output.iter().filter(|x| **x != 0.0).count(), 4);
+
+        println!("✅ Delay bus test passed");
+    }
+
+    #[test]
+    fn test_mixing_is_deterministic() {
+        let mut effects = TrackEffects::default();
+        effects.filter.enabled = true;
+        effects.saturation.enabled = true;
+        effects.transient.enabled = true;
+        effects.transient.sustain = -0.5;
+        effects.bitcrusher.enabled = true;
+        effects.bitcrusher.downsample = 3;
+        effects.reverb_send = 0.3;
+        effects.delay_send = 0.3;
+        let settings = snare_mixer(effects);
+        let input = hits(24000, 6000);
+
+        let mut mixer = mixer_for(&settings);
+        let reference = render(&mut mixer, &input, 4096);
+
+        // Chunking doesn't matter
+        for chunk_size in [1, 64, 441, 1000] {
+            let mut mixer = mixer_for(&settings);
+            assert_eq!(render(&mut mixer, &input, chunk_size), reference);
+        }
+
+        // A reset mixer sounds exactly like a new one
+        mixer.reset();
+        assert_eq!(render(&mut mixer, &input, 4096), reference);
+
+        println!("✅ Deterministic mixing test passed");
+    }
+
+    #[test]
+    fn test_changes_only_build_what_is_new() {
+        let sends = TrackEffects {
+            reverb_send: 0.5,
+            ..TrackEffects::default()
+        };
+        let mut plan = MixerPlan::default();
+        let mut mixer = Mixer::new(48000.0);
+        mixer.apply(&mut plan.prepare(&snare_mixer(sends), 48000.0));
+        let mut input = vec![0.0; 9600];
+        input[..2400].copy_from_slice(&hits(2400, 2400));
+        render(&mut mixer, &input, 512);
+
+        // Turning the send down builds nothing, and the running reverb rings on
+        let quieter = TrackEffects {
+            reverb_send: 0.1,
+            ..TrackEffects::default()
+        };
+        let mut update = plan.prepare(&snare_mixer(quieter), 48000.0);
+        assert!(update.fresh.is_empty() && update.reverb.is_none() && update.delay.is_none());
+        mixer.apply(&mut update);
+        assert!(render(&mut mixer, &[0.0; 480], 480)
+            .iter()
+            .any(|x| x.abs() > 1e-4));
+
+        // A change that never reached the mixer passes what it built to the next one
+        let mut settings = snare_mixer(sends);
+        let kick = TrackEffects {
+            delay_send: 0.5,
+            ..TrackEffects::default()
+        };
+        settings.tracks.insert("kick".to_string(), kick);
+        let unapplied = plan.prepare(&settings, 48000.0);
+        settings.delay_bus.feedback = 0.5;
+        let mut update = plan.prepare(&settings, 48000.0);
+        assert!(update.fresh.is_empty() && update.delay.is_none());
+        update.absorb(unapplied);
+        mixer.apply(&mut update);
+        mixer.begin(480);
+        assert!(mixer.track_buffer("kick").is_some());
+        assert!(mixer.delay.is_some());
+
+        println!("✅ Prepared mixer changes test passed");
+    }
+
+    #[test]
+    fn test_mixer_settings_serialization_defaults() {
+        let settings: MixerSettings =
+            serde_json::from_str(r#"{"tracks":{"snare":{"reverb_send":0.25}}}"#).unwrap();
+        let snare = settings.track("snare");
+        assert_eq!(snare.reverb_send, 0.25);
+        assert!(!snare.filter.enabled);
+        assert_eq!(settings.track("kick"), TrackEffects::default());
+        assert_eq!(settings.delay_bus, DelayBus::default());
+
+        println!("✅ Mixer settings serialization test passed");
+    }
+}
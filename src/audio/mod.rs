@@ -1,6 +1,11 @@
+pub mod backend;
+pub mod effects;
 pub mod engine;
+pub mod mixer;
+pub mod notation;
 pub mod samples;
 pub mod sequencer;
+pub mod synth;
 
 pub use engine::AudioEngine;
 pub use samples::SampleBank;
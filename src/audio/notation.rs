@@ -0,0 +1,287 @@
+//! Text notation for patterns, one pattern per line:
+//!
+//! ```text
+//! Kick (kick):   x...x...x...x...
+//! Snare (snare): ....x.......x..e
+//! hihat:         xexexexexexexexe
+//! ```
+//!
+//! Each line is a label, a colon and one character per step. The label is the sample
+//! name, optionally preceded by a pattern name with the sample in parentheses. `.` is
+//! a rest, `x` a full-velocity hit and `a` to `i` hits at 10% to 90% velocity.
+//! A hit whose velocity falls between those levels is followed by the exact value in
+//! braces, as in `f{0.55}`. Spaces and `|` between steps are ignored, as are blank
+//! lines and `#` comments.
+
+use super::sequencer::{Pattern, Step};
+use anyhow::Result;
+
+const REST: char = '.';
+const FULL_VELOCITY: char = 'x';
+const SOFTEST_VELOCITY: char = 'a'; // 10%, with each following letter 10% louder up to 'i'
+const EXACT_OPEN: char = '{';
+const EXACT_CLOSE: char = '}';
+
+/// Notation character for a step, with the velocity to the nearest 10%
+pub fn step_char(step: &Step) -> char {
+    if !step.active {
+        return REST;
+    }
+    let level = (step.velocity * 10.0).round().clamp(1.0, 10.0) as u8;
+    if level == 10 {
+        FULL_VELOCITY
+    } else {
+        (SOFTEST_VELOCITY as u8 + level - 1) as char
+    }
+}
+
+/// Step for a notation character, or None if the character is not a step
+pub fn char_step(c: char) -> Option<Step> {
+    match c.to_ascii_lowercase() {
+        REST | '-' => Some(Step::new()),
+        FULL_VELOCITY => Some(Step::with_velocity(1.0)),
+        c @ 'a'..='i' => {
+            let level = (c as u8 - SOFTEST_VELOCITY as u8 + 1) as f32;
+            Some(Step::with_velocity(level / 10.0))
+        }
+        _ => None,
+    }
+}
+
+fn label(pattern: &Pattern) -> String {
+    if pattern.name == pattern.sample_name {
+        pattern.sample_name.clone()
+    } else {
+        format!("{} ({})", pattern.name, pattern.sample_name)
+    }
+}
+
+// The step's character, followed by its exact velocity when the character alone loses it
+fn step_text(step: &Step) -> String {
+    let c = step_char(step);
+    match char_step(c) {
+        Some(written) if step.active && written.velocity != step.velocity => {
+            format!("{}{}{}{}", c, EXACT_OPEN, step.velocity, EXACT_CLOSE)
+        }
+        _ => c.to_string(),
+    }
+}
+
+fn steps_text(pattern: &Pattern) -> String {
+    pattern.steps.iter().map(step_text).collect()
+}
+
+/// Write one pattern as a line of notation
+pub fn format_pattern(pattern: &Pattern) -> String {
+    format!("{}: {}", label(pattern), steps_text(pattern))
+}
+
+/// Write patterns one per line, with the steps lined up
+pub fn format_patterns(patterns: &[Pattern]) -> String {
+    let width = patterns
+        .iter()
+        .map(|pattern| label(pattern).chars().count() + 1)
+        .max()
+        .unwrap_or(0);
+
+    patterns
+        .iter()
+        .map(|pattern| {
+            format!(
+                "{:<width$} {}\n",
+                format!("{}:", label(pattern)),
+                steps_text(pattern),
+                width = width
+            )
+        })
+        .collect()
+}
+
+/// Read one line of notation
+pub fn parse_pattern(line: &str) -> Result<Pattern> {
+    let (label, steps_text) = line
+        .rsplit_once(':')
+        .ok_or_else(|| anyhow::anyhow!("Expected 'sample: steps', got '{}'", line.trim()))?;
+
+    let label = label.trim();
+    let (name, sample_name) = match label.strip_suffix(')').and_then(|l| l.rsplit_once('(')) {
+        Some((name, sample_name)) if !name.trim().is_empty() => {
+            (name.trim().to_string(), sample_name.trim().to_string())
+        }
+        _ => (label.to_string(), label.to_string()),
+    };
+    if sample_name.is_empty() {
+        return Err(anyhow::anyhow!("Pattern has no sample name"));
+    }
+
+    let mut steps: Vec<Step> = Vec::new();
+    let mut chars = steps_text
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '|');
+    while let Some(c) = chars.next() {
+        if c == EXACT_OPEN {
+            let mut value = String::new();
+            let mut closed = false;
+            for c in chars.by_ref() {
+                if c == EXACT_CLOSE {
+                    closed = true;
+                    break;
+                }
+                value.push(c);
+            }
+            if !closed {
+                return Err(anyhow::anyhow!(
+                    "Velocity '{}' of '{}' is missing its closing '{}'",
+                    value,
+                    label,
+                    EXACT_CLOSE
+                ));
+            }
+            let velocity = value.parse::<f32>().map_err(|_| {
+                anyhow::anyhow!("Velocity '{}' of '{}' is not a number", value, label)
+            })?;
+            if !(0.0..=1.0).contains(&velocity) {
+                return Err(anyhow::anyhow!(
+                    "Velocity '{}' of '{}' is outside 0.0-1.0",
+                    value,
+                    label
+                ));
+            }
+            match steps.last_mut() {
+                Some(step) if step.active => step.velocity = velocity,
+                _ => {
+                    return Err(anyhow::anyhow!(
+                        "Velocity '{}' of '{}' does not follow a hit",
+                        value,
+                        label
+                    ))
+                }
+            }
+            continue;
+        }
+
+        let step = char_step(c).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Step {} of '{}' is '{}', expected . x or a-i",
+                steps.len() + 1,
+                label,
+                c
+            )
+        })?;
+        steps.push(step);
+    }
+    if steps.is_empty() {
+        return Err(anyhow::anyhow!("Pattern '{}' has no steps", label));
+    }
+
+    Ok(Pattern {
+        name,
+        steps,
+        sample_name,
+    })
+}
+
+/// Read every pattern in a block of notation
+pub fn parse_patterns(text: &str) -> Result<Vec<Pattern>> {
+    let mut patterns = Vec::new();
+    for (line_index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let pattern = parse_pattern(line)
+            .map_err(|err| anyhow::anyhow!("Line {}: {}", line_index + 1, err))?;
+        patterns.push(pattern);
+    }
+
+    if patterns.is_empty() {
+        return Err(anyhow::anyhow!("No patterns found"));
+    }
+    Ok(patterns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_notation_round_trip() {
+        let mut kick = Pattern::new("Kick".to_string(), "kick".to_string(), 16);
+        for index in [0, 4, 8, 12] {
+            kick.set_step(index, Step::with_velocity(1.0));
+        }
+        kick.set_step(14, Step::with_velocity(0.3));
+        let mut hihat = Pattern::new("hihat".to_string(), "hihat".to_string(), 8);
+        hihat.set_step(1, Step::with_velocity(0.55));
+        hihat.set_step(5, Step::with_velocity(0.123));
+
+        let text = format_patterns(&[kick.clone(), hihat.clone()]);
+        assert_eq!(
+            text,
+            "Kick (kick): x...x...x...x.c.\nhihat:       .f{0.55}...a{0.123}..\n"
+        );
+
+        let parsed = parse_patterns(&text).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].name, "Kick");
+        assert_eq!(parsed[0].sample_name, "kick");
+        assert_eq!(parsed[0].steps, kick.steps);
+        assert_eq!(parsed[1].name, "hihat");
+        assert_eq!(parsed[1].steps, hihat.steps);
+        assert_eq!(format_patterns(&parsed), text);
+
+        println!("✅ Pattern notation round-trip test passed");
+    }
+
+    #[test]
+    fn test_pattern_notation_is_forgiving() {
+        let text = "
+            # Four on the floor
+            kick: x... | X... | x... | x...
+
+            Open Hi-Hat (open_hihat): ..x- ..x- ..x- ..x-
+        ";
+        let patterns = parse_patterns(text).unwrap();
+        assert_eq!(patterns.len(), 2);
+        assert_eq!(patterns[0].name, "kick");
+        assert_eq!(patterns[0].steps.len(), 16);
+        assert!(patterns[0].steps[4].active);
+        assert_eq!(patterns[1].name, "Open Hi-Hat");
+        assert_eq!(patterns[1].sample_name, "open_hihat");
+        assert_eq!(
+            format_pattern(&patterns[1]),
+            "Open Hi-Hat (open_hihat): ..x...x...x...x."
+        );
+    }
+
+    #[test]
+    fn test_pattern_notation_errors() {
+        let error = parse_patterns("kick: x...\nsnare: ..q.").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Line 2: Step 3 of 'snare' is 'q', expected . x or a-i"
+        );
+        assert_eq!(
+            parse_patterns("kick: .{0.5}x..").unwrap_err().to_string(),
+            "Line 1: Velocity '0.5' of 'kick' does not follow a hit"
+        );
+        assert!(parse_patterns("kick: x{loud}...").is_err());
+        assert_eq!(
+            parse_patterns("kick: x{5}...").unwrap_err().to_string(),
+            "Line 1: Velocity '5' of 'kick' is outside 0.0-1.0"
+        );
+        assert_eq!(
+            parse_patterns("kick: x{NaN}...").unwrap_err().to_string(),
+            "Line 1: Velocity 'NaN' of 'kick' is outside 0.0-1.0"
+        );
+        assert!(parse_patterns("kick: x{-0.1}...").is_err());
+        assert_eq!(
+            parse_patterns("kick: x...x{0.5").unwrap_err().to_string(),
+            "Line 1: Velocity '0.5' of 'kick' is missing its closing '}'"
+        );
+        assert!(parse_patterns("kick x...").is_err());
+        assert!(parse_patterns("kick:").is_err());
+        assert!(parse_patterns(": x...").is_err());
+        assert!(parse_patterns("# only a comment").is_err());
+    }
+}
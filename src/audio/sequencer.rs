@@ -147,7 +147,7 @@ impl Step {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Pattern {
     pub name: String,
     pub steps: Vec<Step>,
@@ -185,6 +185,15 @@ impl Pattern {
         self.steps.len()
     }
 
+    /// Step that plays `steps_elapsed` steps into a segment. Every pattern loops over its
+    /// own steps, so patterns of different lengths play against each other as polymeters.
+    pub fn step_in_loop(&self, steps_elapsed: usize) -> Option<Step> {
+        if self.steps.is_empty() {
+            return None;
+        }
+        Some(self.steps[steps_elapsed % self.steps.len()])
+    }
+
     pub fn resize(&mut self, new_length: usize) {
         match new_length.cmp(&self.steps.len()) {
             std::cmp::Ordering::Greater => {
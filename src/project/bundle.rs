@@ -0,0 +1,339 @@
+use super::Project;
+use anyhow::{Context, Result};
+use std::collections::{hash_map::Entry, BTreeMap, HashMap};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// Extension of bundle folders holding a project and its samples
+pub const BUNDLE_EXTENSION: &str = "beatrpack";
+
+const BUNDLE_PROJECT_FILE: &str = "project.beatr";
+const BUNDLE_SAMPLES_DIR: &str = "samples";
+
+/// Project file inside a bundle folder
+pub fn bundle_project_path(bundle_dir: &Path) -> PathBuf {
+    bundle_dir.join(BUNDLE_PROJECT_FILE)
+}
+
+/// `path` with the bundle extension added when it doesn't already have it
+pub fn with_bundle_extension(path: PathBuf) -> PathBuf {
+    if path.extension().is_some_and(|ext| ext == BUNDLE_EXTENSION) {
+        return path;
+    }
+    let mut name = path.into_os_string();
+    name.push(".");
+    name.push(BUNDLE_EXTENSION);
+    PathBuf::from(name)
+}
+
+/// Copy every sample file the project uses into `bundle_dir` and save the project
+/// there. Samples with identical content are stored once. On success the project
+/// refers to the bundled copies, and the path of the saved project file is returned.
+/// Of the files already in the folder, only samples an earlier save of the bundle
+/// stored are removed once nothing uses them.
+pub fn save_bundle(project: &mut Project, bundle_dir: &Path) -> Result<PathBuf> {
+    // Read everything first: the sources may already live inside this bundle
+    let mut sources = Vec::with_capacity(project.sample_files.len());
+    for (name, path) in &project.sample_files {
+        let data = std::fs::read(path).with_context(|| {
+            format!(
+                "Sample '{}' could not be read from {}",
+                name,
+                path.display()
+            )
+        })?;
+        sources.push((
+            name.clone(),
+            path.extension().map(|ext| ext.to_owned()),
+            data,
+        ));
+    }
+
+    let samples_dir = bundle_dir.join(BUNDLE_SAMPLES_DIR);
+    let previously_bundled: Vec<PathBuf> = match open_bundle(bundle_dir) {
+        Ok(previous) => previous
+            .sample_files
+            .into_values()
+            .filter(|path| path.parent() == Some(samples_dir.as_path()))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    std::fs::create_dir_all(&samples_dir)
+        .with_context(|| format!("Failed to create bundle at {}", bundle_dir.display()))?;
+
+    let mut bundled = BTreeMap::new();
+    let mut written = HashMap::new();
+    for (name, extension, data) in sources {
+        let hash = sample_content_hash(&data);
+        let file_name = bundled_file_name(hash, extension, &data, &written);
+
+        let bundled_path = samples_dir.join(&file_name);
+        if let Entry::Vacant(entry) = written.entry(file_name) {
+            std::fs::write(&bundled_path, &data)
+                .with_context(|| format!("Failed to write sample '{}' to the bundle", name))?;
+            entry.insert(data);
+        }
+        bundled.insert(name, bundled_path);
+    }
+
+    // Drop samples left over from earlier saves of the same bundle
+    for path in previously_bundled {
+        let still_used = path
+            .file_name()
+            .is_some_and(|file_name| written.contains_key(Path::new(file_name)));
+        if !still_used {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    project.sample_files = bundled;
+    let project_path = bundle_project_path(bundle_dir);
+    project.save_to_file(&project_path)?;
+    Ok(project_path)
+}
+
+/// Load the project stored in a bundle folder
+pub fn open_bundle(bundle_dir: &Path) -> Result<Project> {
+    let project_path = bundle_project_path(bundle_dir);
+    if !project_path.is_file() {
+        return Err(anyhow::anyhow!(
+            "{} is not a Beatr bundle (no {} found)",
+            bundle_dir.display(),
+            BUNDLE_PROJECT_FILE
+        ));
+    }
+    Project::load_from_file(project_path)
+}
+
+// Samples are named after their content hash. The hash can collide, so a name already
+// written with different bytes gets a numbered suffix instead of sharing the file.
+fn bundled_file_name(
+    hash: u64,
+    extension: Option<OsString>,
+    data: &[u8],
+    written: &HashMap<PathBuf, Vec<u8>>,
+) -> PathBuf {
+    let extension = extension.unwrap_or_else(|| "wav".into());
+    let mut suffix = 0;
+    loop {
+        let mut file_name = PathBuf::from(match suffix {
+            0 => format!("{:016x}", hash),
+            n => format!("{:016x}-{}", hash, n),
+        });
+        file_name.set_extension(&extension);
+        match written.get(&file_name) {
+            Some(existing) if existing.as_slice() != data => suffix += 1,
+            _ => return file_name,
+        }
+    }
+}
+
+// 64-bit FNV-1a; stable across platforms and releases, so bundle file names are too
+fn sample_content_hash(data: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    data.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(PRIME)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::samples::Sample;
+    use tempfile::tempdir;
+
+    fn write_wav(path: &Path, frequency: f32) {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(path, spec).unwrap();
+        for i in 0..441 {
+            let t = i as f32 / 44100.0;
+            let value = (2.0 * std::f32::consts::PI * frequency * t).sin();
+            writer
+                .write_sample((value * i16::MAX as f32) as i16)
+                .unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    fn bundled_sample_files(bundle_dir: &Path) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(bundle_dir.join(BUNDLE_SAMPLES_DIR))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn test_bundle_extension_is_added() {
+        assert_eq!(
+            with_bundle_extension(PathBuf::from("/songs/Jam")),
+            PathBuf::from("/songs/Jam.beatrpack")
+        );
+        assert_eq!(
+            with_bundle_extension(PathBuf::from("/songs/Jam v1.2")),
+            PathBuf::from("/songs/Jam v1.2.beatrpack")
+        );
+        assert_eq!(
+            with_bundle_extension(PathBuf::from("/songs/Jam.beatrpack")),
+            PathBuf::from("/songs/Jam.beatrpack")
+        );
+    }
+
+    #[test]
+    fn test_sample_content_hash() {
+        // Reference values of 64-bit FNV-1a
+        assert_eq!(sample_content_hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(sample_content_hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_ne!(sample_content_hash(b"kick"), sample_content_hash(b"kicK"));
+    }
+
+    #[test]
+    fn test_hash_collisions_get_distinct_files() {
+        let mut written = HashMap::new();
+        let first = bundled_file_name(0xbeef, None, b"kick", &written);
+        assert_eq!(first, PathBuf::from("000000000000beef.wav"));
+        written.insert(first.clone(), b"kick".to_vec());
+
+        // The same bytes share the file, different bytes with the same hash do not
+        assert_eq!(bundled_file_name(0xbeef, None, b"kick", &written), first);
+        let second = bundled_file_name(0xbeef, None, b"snare", &written);
+        assert_eq!(second, PathBuf::from("000000000000beef-1.wav"));
+        written.insert(second, b"snare".to_vec());
+        assert_eq!(
+            bundled_file_name(0xbeef, Some("flac".into()), b"clap", &written),
+            PathBuf::from("000000000000beef.flac")
+        );
+        assert_eq!(
+            bundled_file_name(0xbeef, None, b"clap", &written),
+            PathBuf::from("000000000000beef-2.wav")
+        );
+
+        println!("✅ Bundle hash collision test passed");
+    }
+
+    #[test]
+    fn test_bundle_round_trip_between_machines() {
+        let studio = tempdir().unwrap();
+        let kick_path = studio.path().join("my_kick.wav");
+        let kick_copy_path = studio.path().join("kick copy.wav");
+        let snare_path = studio.path().join("snare.wav");
+        write_wav(&kick_path, 60.0);
+        std::fs::copy(&kick_path, &kick_copy_path).unwrap();
+        write_wav(&snare_path, 200.0);
+
+        let mut project = Project::new("Shared Jam".to_string());
+        project
+            .sample_files
+            .insert("big_kick".to_string(), kick_path.clone());
+        project
+            .sample_files
+            .insert("other_kick".to_string(), kick_copy_path);
+        project
+            .sample_files
+            .insert("fat_snare".to_string(), snare_path);
+
+        let bundle_dir = studio.path().join("Shared Jam.beatrpack");
+        let project_path = save_bundle(&mut project, &bundle_dir).unwrap();
+        assert_eq!(project_path, bundle_dir.join("project.beatr"));
+
+        // Identical kicks are stored once, and the project now uses the bundled copies
+        assert_eq!(bundled_sample_files(&bundle_dir).len(), 2);
+        assert_eq!(
+            project.sample_files["big_kick"],
+            project.sample_files["other_kick"]
+        );
+        assert!(project.sample_files["big_kick"].starts_with(&bundle_dir));
+
+        // Paths inside the bundle are saved relative to it
+        let raw: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&project_path).unwrap()).unwrap();
+        assert!(raw["sample_files"]["fat_snare"]
+            .as_str()
+            .unwrap()
+            .starts_with("samples"));
+
+        // Move the bundle somewhere else and lose the originals
+        let other_machine = tempdir().unwrap();
+        let moved_dir = other_machine.path().join("Shared Jam.beatrpack");
+        std::fs::rename(&bundle_dir, &moved_dir).unwrap();
+        drop(studio);
+
+        let opened = open_bundle(&moved_dir).unwrap();
+        assert_eq!(opened.metadata.name, "Shared Jam");
+        assert_eq!(opened.sample_files.len(), 3);
+        for path in opened.sample_files.values() {
+            assert!(path.starts_with(&moved_dir));
+            assert_eq!(Sample::from_wav_file(path).unwrap().len(), 441);
+        }
+
+        println!("✅ Bundle round-trip test passed");
+    }
+
+    #[test]
+    fn test_resaving_bundle_replaces_unused_samples() {
+        let dir = tempdir().unwrap();
+        let kick_path = dir.path().join("kick.wav");
+        let snare_path = dir.path().join("snare.wav");
+        write_wav(&kick_path, 60.0);
+        write_wav(&snare_path, 200.0);
+
+        let mut project = Project::new("Resave".to_string());
+        project.sample_files.insert("kick".to_string(), kick_path);
+        project.sample_files.insert("snare".to_string(), snare_path);
+
+        let bundle_dir = dir.path().join("resave.beatrpack");
+        save_bundle(&mut project, &bundle_dir).unwrap();
+        // Files the bundle didn't put there are not its to delete
+        let notes_path = bundle_dir.join(BUNDLE_SAMPLES_DIR).join("notes.txt");
+        std::fs::write(&notes_path, "recorded at 96k").unwrap();
+
+        // Saving an opened bundle again reads its own samples
+        let mut reopened = open_bundle(&bundle_dir).unwrap();
+        reopened.sample_files.remove("snare");
+        save_bundle(&mut reopened, &bundle_dir).unwrap();
+
+        let mut expected = vec![reopened.sample_files["kick"].clone(), notes_path];
+        expected.sort();
+        assert_eq!(bundled_sample_files(&bundle_dir), expected);
+        assert!(open_bundle(&bundle_dir).is_ok());
+
+        println!("✅ Bundle resave test passed");
+    }
+
+    #[test]
+    fn test_bundle_errors() {
+        let dir = tempdir().unwrap();
+
+        let mut project = Project::new("Missing".to_string());
+        project
+            .sample_files
+            .insert("ghost".to_string(), dir.path().join("ghost.wav"));
+        let error = save_bundle(&mut project, &dir.path().join("missing.beatrpack"))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("'ghost'"), "{}", error);
+        // Nothing changes when collecting fails
+        assert_eq!(project.sample_files["ghost"], dir.path().join("ghost.wav"));
+
+        let error = open_bundle(dir.path()).unwrap_err().to_string();
+        assert!(error.contains("not a Beatr bundle"), "{}", error);
+
+        // Saving into a folder that isn't a bundle leaves its files alone
+        let folder = dir.path().join("Music");
+        std::fs::create_dir_all(folder.join(BUNDLE_SAMPLES_DIR)).unwrap();
+        let keep = folder.join(BUNDLE_SAMPLES_DIR).join("keep.wav");
+        write_wav(&keep, 440.0);
+        save_bundle(&mut Project::new("Plain".to_string()), &folder).unwrap();
+        assert_eq!(bundled_sample_files(&folder), vec![keep]);
+
+        println!("✅ Bundle error test passed");
+    }
+}
@@ -0,0 +1,254 @@
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+
+/// File format written by this version of Beatr
+pub const CURRENT_FORMAT_VERSION: u32 = 8;
+
+// Files saved before the format version was recorded
+const LEGACY_FORMAT_VERSION: u32 = 1;
+
+pub(crate) fn legacy_format_version() -> u32 {
+    LEGACY_FORMAT_VERSION
+}
+
+type Migration = fn(&mut Map<String, Value>) -> Result<()>;
+
+// MIGRATIONS[n] upgrades a project from format n + 1 to format n + 2
+const MIGRATIONS: [Migration; (CURRENT_FORMAT_VERSION - LEGACY_FORMAT_VERSION) as usize] = [
+    migrate_v1_to_v2,
+    migrate_v2_to_v3,
+    migrate_v3_to_v4,
+    migrate_v4_to_v5,
+    migrate_v5_to_v6,
+    migrate_v6_to_v7,
+    migrate_v7_to_v8,
+];
+
+/// Read the format version of raw project JSON; files without one are legacy files
+pub fn format_version_of(project: &Value) -> Result<u32> {
+    match project.get("format_version") {
+        None => Ok(LEGACY_FORMAT_VERSION),
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|&v| v >= LEGACY_FORMAT_VERSION)
+            .ok_or_else(|| anyhow::anyhow!("Invalid project format version: {}", version)),
+    }
+}
+
+/// Upgrade raw project JSON to the current format, one version at a time
+pub fn migrate(mut project: Value) -> Result<Value> {
+    let version = format_version_of(&project)?;
+    if version > CURRENT_FORMAT_VERSION {
+        return Err(anyhow::anyhow!(
+            "This project was saved by a newer version of Beatr (file format {}, this version supports up to {}). Please update Beatr to open it.",
+            version,
+            CURRENT_FORMAT_VERSION
+        ));
+    }
+
+    let fields = project
+        .as_object_mut()
+        .ok_or_else(|| anyhow::anyhow!("Project file must contain a JSON object"))?;
+
+    for (from_version, step) in
+        (version..).zip(&MIGRATIONS[(version - LEGACY_FORMAT_VERSION) as usize..])
+    {
+        step(fields).with_context(|| {
+            format!(
+                "Failed to upgrade project from format {} to {}",
+                from_version,
+                from_version + 1
+            )
+        })?;
+    }
+
+    fields.insert("format_version".to_string(), CURRENT_FORMAT_VERSION.into());
+    Ok(project)
+}
+
+// Format 1 predates loop regions and markers; write out their defaults
+fn migrate_v1_to_v2(project: &mut Map<String, Value>) -> Result<()> {
+    let timeline = project
+        .get_mut("timeline")
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| anyhow::anyhow!("Project has no timeline"))?;
+
+    timeline.entry("loop_region").or_insert(Value::Null);
+    timeline
+        .entry("markers")
+        .or_insert_with(|| Value::Array(Vec::new()));
+    Ok(())
+}
+
+// Format 2 only used built-in samples
+fn migrate_v2_to_v3(project: &mut Map<String, Value>) -> Result<()> {
+    project
+        .entry("sample_files")
+        .or_insert_with(|| Value::Object(Map::new()));
+    Ok(())
+}
+
+// Format 3 took segment defaults from the app settings; start from the first segment instead
+fn migrate_v3_to_v4(project: &mut Map<String, Value>) -> Result<()> {
+    let first_segment = project
+        .get("timeline")
+        .and_then(|timeline| timeline.get("segments"))
+        .and_then(|segments| segments.get(0));
+    let time_signature = first_segment
+        .and_then(|segment| segment.get("time_signature"))
+        .cloned()
+        .unwrap_or_else(|| serde_json::json!({"numerator": 4, "denominator": 4}));
+    let pattern_length = first_segment
+        .and_then(|segment| segment.pointer("/patterns/0/steps"))
+        .and_then(Value::as_array)
+        .map_or(16, |steps| steps.len().clamp(4, 64));
+
+    project.entry("defaults").or_insert_with(|| {
+        serde_json::json!({
+            "time_signature": time_signature,
+            "pattern_length": pattern_length,
+        })
+    });
+    Ok(())
+}
+
+// Format 4 had no master effects; files load with every effect off, so they sound the same
+fn migrate_v4_to_v5(_project: &mut Map<String, Value>) -> Result<()> {
+    Ok(())
+}
+
+// Format 5 had no track mixer; files load with no inserts and dry sends, so they sound the same
+fn migrate_v5_to_v6(_project: &mut Map<String, Value>) -> Result<()> {
+    Ok(())
+}
+
+// Format 6 had no synthesized voices; files load playing the built-in sounds as before
+fn migrate_v6_to_v7(_project: &mut Map<String, Value>) -> Result<()> {
+    Ok(())
+}
+
+// Format 7 had no kit seed; the default seed renders the sounds those files were made with
+fn migrate_v7_to_v8(_project: &mut Map<String, Value>) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::Project;
+    use tempfile::tempdir;
+
+    // Frozen copy of example_project.beatr as written by format 1
+    const FORMAT_V1_FIXTURE: &str = include_str!("fixtures/format_v1_example.beatr");
+
+    #[test]
+    fn test_format_v1_fixture_upgrades() {
+        let raw: Value = serde_json::from_str(FORMAT_V1_FIXTURE).unwrap();
+        assert_eq!(format_version_of(&raw).unwrap(), 1);
+
+        let project = Project::from_json(FORMAT_V1_FIXTURE).unwrap();
+        assert_eq!(project.format_version, CURRENT_FORMAT_VERSION);
+        assert_eq!(project.metadata.name, "Example Drum Track");
+        assert_eq!(project.metadata.author, Some("Beatr User".to_string()));
+        assert_eq!(
+            project.timeline.segments.len(),
+            raw["timeline"]["segments"].as_array().unwrap().len()
+        );
+        assert_eq!(project.timeline.segments[0].pattern_id, "Main Beat");
+        assert_eq!(project.timeline.segments[0].patterns[0].sample_name, "kick");
+        assert!(project.timeline.segments[0].patterns[0].steps[0].active);
+        assert!(project.timeline.markers.is_empty());
+        assert!(project.timeline.loop_region.is_none());
+        assert!(project.sample_files.is_empty());
+        assert_eq!(
+            project.defaults.time_signature,
+            project.timeline.segments[0].time_signature
+        );
+        assert_eq!(project.defaults.pattern_length, 16);
+        assert_eq!(project.synth_seed, crate::audio::synth::DEFAULT_SYNTH_SEED);
+        assert!(project.synth_voices.is_empty());
+        assert_eq!(project.mixer, crate::audio::mixer::MixerSettings::default());
+        assert_eq!(
+            project.master_effects,
+            crate::audio::effects::MasterEffects::default()
+        );
+        assert!(project.validate().is_ok());
+
+        println!("✅ Format 1 fixture upgrade test passed");
+    }
+
+    #[test]
+    fn test_example_project_loads() {
+        let project = Project::from_json(include_str!("../../example_project.beatr")).unwrap();
+        assert_eq!(project.format_version, CURRENT_FORMAT_VERSION);
+        assert!(project.validate().is_ok());
+
+        println!("✅ Example project load test passed");
+    }
+
+    #[test]
+    fn test_upgraded_project_round_trip() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("upgraded.beatr");
+
+        let mut project = Project::from_json(FORMAT_V1_FIXTURE).unwrap();
+        project.save_to_file(&file_path).unwrap();
+
+        // Saved files carry the current version and need no further upgrades
+        let saved: Value =
+            serde_json::from_str(&std::fs::read_to_string(&file_path).unwrap()).unwrap();
+        assert_eq!(format_version_of(&saved).unwrap(), CURRENT_FORMAT_VERSION);
+        assert_eq!(migrate(saved.clone()).unwrap(), saved);
+
+        let reloaded = Project::load_from_file(&file_path).unwrap();
+        assert_eq!(
+            reloaded.timeline.segments.len(),
+            project.timeline.segments.len()
+        );
+        assert_eq!(reloaded.metadata.name, project.metadata.name);
+
+        println!("✅ Upgraded project round-trip test passed");
+    }
+
+    #[test]
+    fn test_format_v3_defaults_without_segments() {
+        let raw = serde_json::json!({
+            "format_version": 3,
+            "metadata": Project::default().metadata,
+            "timeline": {"segments": [], "current_position": 0.0, "playback_state": "Stopped"},
+            "global_bpm": 100.0,
+            "global_volume": 1.0,
+        });
+
+        let project = Project::from_json(&raw.to_string()).unwrap();
+        assert_eq!(project.defaults, crate::project::ProjectDefaults::default());
+        assert!(project.validate().is_ok());
+
+        println!("✅ Format 3 defaults upgrade test passed");
+    }
+
+    #[test]
+    fn test_newer_format_is_rejected() {
+        let mut raw: Value = serde_json::from_str(FORMAT_V1_FIXTURE).unwrap();
+        raw["format_version"] = (CURRENT_FORMAT_VERSION + 1).into();
+
+        let error = Project::from_json(&raw.to_string())
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("newer version of Beatr"), "{}", error);
+
+        // Versions that are not positive integers are rejected too
+        for invalid in [Value::from(0), Value::from("2"), Value::from(-1)] {
+            raw["format_version"] = invalid;
+            assert!(Project::from_json(&raw.to_string()).is_err());
+        }
+
+        // A format 1 file without a timeline cannot be upgraded
+        let broken = serde_json::json!({"metadata": {}, "global_bpm": 120.0});
+        let error = migrate(broken).unwrap_err();
+        assert!(format!("{:#}", error).contains("from format 1 to 2"));
+
+        println!("✅ Newer format rejection test passed");
+    }
+}
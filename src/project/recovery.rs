@@ -0,0 +1,200 @@
+use super::Project;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+const AUTOSAVE_FILE_NAME: &str = "autosave.beatr";
+const AUTOSAVE_INFO_FILE_NAME: &str = "autosave.json";
+
+/// Where an autosave came from, stored next to the autosaved project
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecoveryInfo {
+    pub project_path: Option<PathBuf>, // None for projects that were never saved
+    pub autosaved_at: String,
+}
+
+/// An autosaved project that is newer than its last explicit save
+#[derive(Debug, Clone)]
+pub struct RecoveredProject {
+    pub project: Project,
+    pub info: RecoveryInfo,
+}
+
+/// Autosave snapshots kept until the project is saved or the app exits cleanly
+#[derive(Debug, Clone)]
+pub struct RecoveryStore {
+    dir: PathBuf,
+}
+
+impl RecoveryStore {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        RecoveryStore { dir: dir.into() }
+    }
+
+    /// Recovery directory inside the application's config directory
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open_default() -> Result<Self> {
+        Ok(Self::new(
+            crate::settings::AppSettings::get_config_dir()?.join("recovery"),
+        ))
+    }
+
+    fn autosave_path(&self) -> PathBuf {
+        self.dir.join(AUTOSAVE_FILE_NAME)
+    }
+
+    fn info_path(&self) -> PathBuf {
+        self.dir.join(AUTOSAVE_INFO_FILE_NAME)
+    }
+
+    /// Write a snapshot of `project`, which belongs to `project_path` if it has been saved before
+    pub fn write(&self, project: &Project, project_path: Option<&Path>) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+
+        let info = RecoveryInfo {
+            project_path: project_path.map(Path::to_path_buf),
+            autosaved_at: chrono::Utc::now()
+                .format("%Y-%m-%d %H:%M:%S UTC")
+                .to_string(),
+        };
+
+        // Write to a temporary file first so a crash mid-write never leaves a broken autosave
+        let temp_path = self.dir.join(format!("{}.tmp", AUTOSAVE_FILE_NAME));
+        std::fs::write(&temp_path, serde_json::to_string_pretty(project)?)?;
+        std::fs::rename(&temp_path, self.autosave_path())?;
+        std::fs::write(self.info_path(), serde_json::to_string_pretty(&info)?)?;
+        Ok(())
+    }
+
+    /// Find an autosave worth offering. Autosaves older than their project file were
+    /// superseded by an explicit save and are removed.
+    pub fn find_recoverable(&self) -> Option<RecoveredProject> {
+        let autosave_path = self.autosave_path();
+        let autosaved_at = modified_time(&autosave_path)?;
+
+        let info: RecoveryInfo = std::fs::read_to_string(self.info_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or(RecoveryInfo {
+                project_path: None,
+                autosaved_at: String::new(),
+            });
+
+        if let Some(saved_at) = info.project_path.as_deref().and_then(modified_time) {
+            if saved_at >= autosaved_at {
+                let _ = self.clear();
+                return None;
+            }
+        }
+
+        let content = std::fs::read_to_string(&autosave_path).ok()?;
+        match Project::from_json(&content) {
+            Ok(project) => Some(RecoveredProject { project, info }),
+            Err(err) => {
+                eprintln!("Warning: Ignoring unreadable autosave: {}", err);
+                None
+            }
+        }
+    }
+
+    /// Remove the autosave, e.g. after an explicit save or a clean exit
+    pub fn clear(&self) -> Result<()> {
+        for path in [self.autosave_path(), self.info_path()] {
+            if path.exists() {
+                std::fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    fn set_modified(path: &Path, time: SystemTime) {
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(time)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_autosave_of_unsaved_project_is_recoverable() {
+        let dir = tempdir().unwrap();
+        let store = RecoveryStore::new(dir.path().join("recovery"));
+        assert!(store.find_recoverable().is_none());
+
+        let mut project = Project::new("Crashed Jam".to_string());
+        project.global_bpm = 133.0;
+        project.timeline.add_marker("Drop", 8.0);
+        store.write(&project, None).unwrap();
+
+        let recovered = store.find_recoverable().unwrap();
+        assert_eq!(recovered.project.metadata.name, "Crashed Jam");
+        assert_eq!(recovered.project.global_bpm, 133.0);
+        assert_eq!(recovered.project.timeline.markers.len(), 1);
+        assert_eq!(recovered.info.project_path, None);
+        assert!(!recovered.info.autosaved_at.is_empty());
+
+        store.clear().unwrap();
+        assert!(store.find_recoverable().is_none());
+        // Clearing twice is fine
+        store.clear().unwrap();
+
+        println!("✅ Unsaved project recovery test passed");
+    }
+
+    #[test]
+    fn test_autosave_older_than_explicit_save_is_discarded() {
+        let dir = tempdir().unwrap();
+        let store = RecoveryStore::new(dir.path().join("recovery"));
+        let project_path = dir.path().join("song.beatr");
+
+        let mut project = Project::new("Song".to_string());
+        project.save_to_file(&project_path).unwrap();
+        store.write(&project, Some(&project_path)).unwrap();
+
+        // Autosave is newer than the last explicit save: offer it
+        let now = SystemTime::now();
+        set_modified(&project_path, now - Duration::from_secs(60));
+        set_modified(&store.autosave_path(), now);
+        let recovered = store.find_recoverable().unwrap();
+        assert_eq!(recovered.info.project_path, Some(project_path.clone()));
+
+        // Project saved after the autosave: the autosave is stale and removed
+        set_modified(&project_path, now + Duration::from_secs(60));
+        assert!(store.find_recoverable().is_none());
+        assert!(!store.autosave_path().exists());
+
+        println!("✅ Stale autosave test passed");
+    }
+
+    #[test]
+    fn test_corrupt_autosave_is_ignored() {
+        let dir = tempdir().unwrap();
+        let store = RecoveryStore::new(dir.path());
+
+        std::fs::write(store.autosave_path(), "{ not json").unwrap();
+        assert!(store.find_recoverable().is_none());
+
+        // A missing info file still lets the project be recovered
+        store
+            .write(&Project::new("No Info".to_string()), None)
+            .unwrap();
+        std::fs::remove_file(store.info_path()).unwrap();
+        let recovered = store.find_recoverable().unwrap();
+        assert_eq!(recovered.project.metadata.name, "No Info");
+
+        println!("✅ Corrupt autosave test passed");
+    }
+}
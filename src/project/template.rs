@@ -0,0 +1,313 @@
+use super::{Project, ProjectMetadata, CURRENT_FORMAT_VERSION};
+use crate::audio::{sequencer::Pattern, Step, TimeSignature};
+use crate::timeline::{PlaybackState, TimelineSegment};
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// A starting point for new projects: segments, sample kit, mixer state and tempo
+#[derive(Debug, Clone)]
+pub struct ProjectTemplate {
+    pub name: String,
+    pub description: String,
+    pub builtin: bool,
+    project: Project,
+}
+
+impl ProjectTemplate {
+    /// Capture everything in `project` except its identity and playback state
+    pub fn from_project(name: &str, description: &str, project: &Project) -> Self {
+        let mut project = project.clone();
+        project.metadata = ProjectMetadata {
+            name: name.to_string(),
+            description: Some(description.to_string()).filter(|d| !d.is_empty()),
+            ..ProjectMetadata::default()
+        };
+        project.timeline.current_position = 0.0;
+        project.timeline.playback_state = PlaybackState::Stopped;
+
+        ProjectTemplate {
+            name: name.to_string(),
+            description: description.to_string(),
+            builtin: false,
+            project,
+        }
+    }
+
+    /// Create a new project from this template
+    pub fn instantiate(&self, project_name: String) -> Project {
+        let mut project = self.project.clone();
+        project.format_version = CURRENT_FORMAT_VERSION;
+        project.metadata = ProjectMetadata {
+            name: project_name,
+            ..ProjectMetadata::default()
+        };
+        project
+    }
+
+    /// The project this template creates, for previews
+    pub fn project(&self) -> &Project {
+        &self.project
+    }
+}
+
+/// Genre templates that ship with Beatr
+pub fn builtin_templates() -> Vec<ProjectTemplate> {
+    vec![
+        builtin(
+            "Rock",
+            "Straight eighth-note hi-hats with kick and backbeat snare",
+            120.0,
+            &[
+                ("Kick", "kick", "x.......x.x....."),
+                ("Snare", "snare", "....x.......x..."),
+                ("Hi-Hat", "hihat", "x.x.x.x.x.x.x.x."),
+                ("Crash", "crash", "x..............."),
+            ],
+        ),
+        builtin(
+            "House",
+            "Four-on-the-floor kick, clap on two and four, off-beat open hats",
+            124.0,
+            &[
+                ("Kick", "kick", "x...x...x...x..."),
+                ("Clap", "clap", "....x.......x..."),
+                ("Hi-Hat", "hihat", "xxxxxxxxxxxxxxxx"),
+                ("Open Hi-Hat", "open_hihat", "..x...x...x...x."),
+            ],
+        ),
+        builtin(
+            "Hip-Hop",
+            "Laid-back boom bap groove with swung kicks and rim shots",
+            90.0,
+            &[
+                ("Kick", "kick", "x......x..x....."),
+                ("Snare", "snare", "....x.......x..."),
+                ("Hi-Hat", "hihat", "x.x.x.x.x.x.x.xx"),
+                ("Rim Shot", "rimshot", "...........x...x"),
+            ],
+        ),
+        builtin(
+            "Drum & Bass",
+            "Two-step breakbeat at 174 BPM",
+            174.0,
+            &[
+                ("Kick", "kick", "x.........x....."),
+                ("Snare", "snare", "....x.......x..."),
+                ("Hi-Hat", "hihat", "x.x.x.x.x.x.x.x."),
+                ("Tom", "tom", ".............x.x"),
+            ],
+        ),
+    ]
+}
+
+// One four-bar 4/4 segment with a pattern per (name, sample, steps) row; `x` marks an active step
+fn builtin(
+    name: &str,
+    description: &str,
+    bpm: f32,
+    rows: &[(&str, &str, &str)],
+) -> ProjectTemplate {
+    let patterns = rows
+        .iter()
+        .map(|(pattern_name, sample_name, steps)| {
+            let mut pattern = Pattern::new(
+                pattern_name.to_string(),
+                sample_name.to_string(),
+                steps.len(),
+            );
+            for (index, _) in steps.chars().enumerate().filter(|(_, c)| *c == 'x') {
+                pattern.set_step(index, Step::with_velocity(1.0));
+            }
+            pattern
+        })
+        .collect();
+
+    let mut project = Project::new(name.to_string());
+    project.global_bpm = bpm;
+    project.timeline.add_segment(TimelineSegment::new(
+        "Main Groove".to_string(),
+        patterns,
+        0.0,
+        4,
+        TimeSignature::four_four(),
+        bpm,
+    ));
+
+    let mut template = ProjectTemplate::from_project(name, description, &project);
+    template.builtin = true;
+    template
+}
+
+/// User templates, stored as project files in a folder
+#[derive(Debug, Clone)]
+pub struct TemplateStore {
+    dir: PathBuf,
+}
+
+impl TemplateStore {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        TemplateStore { dir: dir.into() }
+    }
+
+    /// Templates directory inside the application's config directory
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open_default() -> Result<Self> {
+        Ok(Self::new(
+            crate::settings::AppSettings::get_config_dir()?.join("templates"),
+        ))
+    }
+
+    fn template_path(&self, name: &str) -> PathBuf {
+        let file_name: String = name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == ' ' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.dir
+            .join(file_name.trim())
+            .with_extension(Project::file_extension())
+    }
+
+    /// Save `template`, replacing any user template with the same name
+    pub fn save(&self, template: &ProjectTemplate) -> Result<PathBuf> {
+        if template.name.trim().is_empty() {
+            return Err(anyhow::anyhow!("Template name cannot be empty"));
+        }
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+
+        let path = self.template_path(&template.name);
+        let mut project = template.project.clone();
+        project.save_to_file(&path)?;
+        Ok(path)
+    }
+
+    /// All user templates, sorted by name. Unreadable files are skipped.
+    pub fn list(&self) -> Vec<ProjectTemplate> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut templates: Vec<ProjectTemplate> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| Project::is_project_file(path))
+            .filter_map(|path| match load_template(&path) {
+                Ok(template) => Some(template),
+                Err(err) => {
+                    eprintln!(
+                        "Warning: Ignoring unreadable template {}: {}",
+                        path.display(),
+                        err
+                    );
+                    None
+                }
+            })
+            .collect();
+        templates.sort_by_key(|template| template.name.to_lowercase());
+        templates
+    }
+
+    /// Remove the user template called `name`
+    pub fn delete(&self, name: &str) -> Result<()> {
+        let path = self.template_path(name);
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+fn load_template(path: &Path) -> Result<ProjectTemplate> {
+    let project = Project::load_from_file(path)?;
+    Ok(ProjectTemplate {
+        name: project.metadata.name.clone(),
+        description: project.metadata.description.clone().unwrap_or_default(),
+        builtin: false,
+        project,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_builtin_templates_are_valid() {
+        let templates = builtin_templates();
+        assert!(templates.len() >= 3);
+
+        for template in &templates {
+            assert!(template.builtin);
+            let project = template.instantiate("From Template".to_string());
+            assert_eq!(project.metadata.name, "From Template");
+            assert_eq!(project.timeline.segments.len(), 1);
+            assert!(project.validate().is_ok(), "{} is invalid", template.name);
+        }
+
+        let house = templates.iter().find(|t| t.name == "House").unwrap();
+        let project = house.instantiate("Club".to_string());
+        assert_eq!(project.global_bpm, 124.0);
+        assert_eq!(project.timeline.segments[0].bpm, 124.0);
+        assert!(project.timeline.segments[0].patterns[0].steps[4].active);
+
+        println!("✅ Built-in templates test passed");
+    }
+
+    #[test]
+    fn test_user_template_round_trip() {
+        let dir = tempdir().unwrap();
+        let store = TemplateStore::new(dir.path().join("templates"));
+        assert!(store.list().is_empty());
+
+        let mut project = builtin_templates()[0].instantiate("My Song".to_string());
+        project.global_volume = 0.7;
+        project.sample_files.insert(
+            "kick".to_string(),
+            dir.path().join("samples").join("kick.wav"),
+        );
+        project.timeline.play();
+        project.timeline.current_position = 3.0;
+
+        let template = ProjectTemplate::from_project("My Kit: Live", "Live room kit", &project);
+        store.save(&template).unwrap();
+
+        let templates = store.list();
+        assert_eq!(templates.len(), 1);
+        assert_eq!(templates[0].name, "My Kit: Live");
+        assert_eq!(templates[0].description, "Live room kit");
+        assert!(!templates[0].builtin);
+
+        // Segments, kit, mixer state and tempo carry over; playback state does not
+        let created = templates[0].instantiate("Next Song".to_string());
+        assert_eq!(created.metadata.name, "Next Song");
+        assert_eq!(created.metadata.description, None);
+        assert_eq!(created.global_bpm, project.global_bpm);
+        assert_eq!(created.global_volume, 0.7);
+        assert_eq!(created.sample_files, project.sample_files);
+        assert_eq!(created.timeline.segments.len(), 1);
+        assert_eq!(created.timeline.current_position, 0.0);
+        assert!(!created.timeline.is_playing());
+
+        // Saving under the same name replaces the template
+        store
+            .save(&ProjectTemplate::from_project("My Kit: Live", "", &project))
+            .unwrap();
+        assert_eq!(store.list().len(), 1);
+
+        store.delete("My Kit: Live").unwrap();
+        assert!(store.list().is_empty());
+        assert!(store
+            .save(&ProjectTemplate::from_project(" ", "", &project))
+            .is_err());
+
+        println!("✅ User template round-trip test passed");
+    }
+}
@@ -0,0 +1,641 @@
+use super::{Project, ProjectDefaults};
+use crate::audio::samples::DEFAULT_SAMPLE_NAMES;
+use crate::audio::{Step, TimeSignature};
+use crate::timeline::LoopRegion;
+use std::collections::HashSet;
+use std::fmt;
+
+// Segments overlapping by less than this are treated as touching
+const OVERLAP_EPSILON: f64 = 1e-6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning, // The project plays, but probably not as intended
+    Error,   // The project cannot be used until this is fixed
+}
+
+/// Where in a project an issue was found; unset fields are not part of the location
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IssueLocation {
+    pub segment_id: Option<String>,
+    pub pattern_name: Option<String>,
+    pub step_index: Option<usize>,
+    pub marker_name: Option<String>,
+}
+
+impl IssueLocation {
+    pub fn project() -> Self {
+        Self::default()
+    }
+
+    pub fn segment(segment_id: &str) -> Self {
+        IssueLocation {
+            segment_id: Some(segment_id.to_string()),
+            ..Self::default()
+        }
+    }
+
+    pub fn pattern(segment_id: &str, pattern_name: &str) -> Self {
+        IssueLocation {
+            pattern_name: Some(pattern_name.to_string()),
+            ..Self::segment(segment_id)
+        }
+    }
+
+    pub fn step(segment_id: &str, pattern_name: &str, step_index: usize) -> Self {
+        IssueLocation {
+            step_index: Some(step_index),
+            ..Self::pattern(segment_id, pattern_name)
+        }
+    }
+
+    pub fn marker(marker_name: &str) -> Self {
+        IssueLocation {
+            marker_name: Some(marker_name.to_string()),
+            ..Self::default()
+        }
+    }
+}
+
+impl fmt::Display for IssueLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(segment_id) = &self.segment_id {
+            parts.push(format!("segment '{}'", segment_id));
+        }
+        if let Some(pattern_name) = &self.pattern_name {
+            parts.push(format!("pattern '{}'", pattern_name));
+        }
+        if let Some(step_index) = self.step_index {
+            parts.push(format!("step {}", step_index + 1));
+        }
+        if let Some(marker_name) = &self.marker_name {
+            parts.push(format!("marker '{}'", marker_name));
+        }
+
+        if parts.is_empty() {
+            write!(f, "project")
+        } else {
+            write!(f, "{}", parts.join(", "))
+        }
+    }
+}
+
+/// One problem found in a project
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    pub location: IssueLocation,
+    pub message: String,
+    pub fixable: bool, // Project::repair can fix this without losing work
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+/// Every issue found in a project, in timeline order
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationReport {
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    fn push(
+        &mut self,
+        severity: Severity,
+        location: IssueLocation,
+        message: String,
+        fixable: bool,
+    ) {
+        self.issues.push(ValidationIssue {
+            severity,
+            location,
+            message,
+            fixable,
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// True when nothing prevents the project from being used
+    pub fn is_valid(&self) -> bool {
+        self.first_error().is_none()
+    }
+
+    pub fn first_error(&self) -> Option<&ValidationIssue> {
+        self.issues
+            .iter()
+            .find(|issue| issue.severity == Severity::Error)
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Warning)
+    }
+
+    pub fn has_fixes(&self) -> bool {
+        self.issues.iter().any(|issue| issue.fixable)
+    }
+
+    /// True when Project::repair leaves no errors behind
+    pub fn is_repairable(&self) -> bool {
+        self.errors().all(|issue| issue.fixable)
+    }
+}
+
+fn bpm_in_range(bpm: f32) -> bool {
+    (60.0..=300.0).contains(&bpm)
+}
+
+fn time_signature_is_valid(time_signature: &TimeSignature) -> bool {
+    TimeSignature::new(time_signature.numerator, time_signature.denominator).is_ok()
+}
+
+fn velocity_in_range(step: &Step) -> bool {
+    (0.0..=1.0).contains(&step.velocity)
+}
+
+/// Check the whole project, collecting every issue instead of stopping at the first
+pub fn check(project: &Project) -> ValidationReport {
+    use Severity::{Error, Warning};
+
+    let mut report = ValidationReport::default();
+    let here = IssueLocation::project;
+
+    if project.metadata.name.trim().is_empty() {
+        report.push(
+            Error,
+            here(),
+            "Project name cannot be empty".to_string(),
+            true,
+        );
+    }
+    if !bpm_in_range(project.global_bpm) {
+        report.push(
+            Error,
+            here(),
+            format!("Global BPM {} is outside 60-300", project.global_bpm),
+            true,
+        );
+    }
+    if !(0.0..=2.0).contains(&project.global_volume) {
+        report.push(
+            Error,
+            here(),
+            format!("Global volume {} is outside 0.0-2.0", project.global_volume),
+            true,
+        );
+    }
+
+    let defaults = project.defaults;
+    if !time_signature_is_valid(&defaults.time_signature) {
+        report.push(
+            Error,
+            here(),
+            format!(
+                "Default time signature {}/{} is invalid",
+                defaults.time_signature.numerator, defaults.time_signature.denominator
+            ),
+            true,
+        );
+    }
+    if !(4..=64).contains(&defaults.pattern_length) {
+        report.push(
+            Error,
+            here(),
+            format!(
+                "Default pattern length {} is outside 4-64 steps",
+                defaults.pattern_length
+            ),
+            true,
+        );
+    }
+
+    let known_samples: HashSet<&str> = DEFAULT_SAMPLE_NAMES
+        .iter()
+        .copied()
+        .chain(project.sample_files.keys().map(String::as_str))
+        .chain(project.synth_voices.keys().map(String::as_str))
+        .collect();
+
+    for segment in &project.timeline.segments {
+        let at_segment = || IssueLocation::segment(&segment.id);
+
+        if !(segment.start_time.is_finite() && segment.start_time >= 0.0) {
+            report.push(
+                Error,
+                at_segment(),
+                format!(
+                    "Start time {} is before the timeline start",
+                    segment.start_time
+                ),
+                true,
+            );
+        }
+        if !bpm_in_range(segment.bpm) {
+            report.push(
+                Error,
+                at_segment(),
+                format!("BPM {} is outside 60-300", segment.bpm),
+                true,
+            );
+        }
+        if segment.loop_count == 0 {
+            report.push(
+                Error,
+                at_segment(),
+                "Loop count must be at least 1".to_string(),
+                true,
+            );
+        }
+        if !time_signature_is_valid(&segment.time_signature) {
+            report.push(
+                Error,
+                at_segment(),
+                format!(
+                    "Time signature {}/{} is invalid",
+                    segment.time_signature.numerator, segment.time_signature.denominator
+                ),
+                true,
+            );
+        }
+        if !(segment.duration.is_finite() && segment.duration > 0.0) {
+            report.push(
+                Error,
+                at_segment(),
+                format!("Duration {} is not a positive length", segment.duration),
+                true,
+            );
+        }
+
+        // Mixed lengths are reported but not repaired, since they may be a deliberate polymeter
+        let longest = segment
+            .patterns
+            .iter()
+            .map(|pattern| pattern.steps.len())
+            .max()
+            .unwrap_or(0);
+        for pattern in &segment.patterns {
+            let at_pattern = || IssueLocation::pattern(&segment.id, &pattern.name);
+
+            if !known_samples.contains(pattern.sample_name.as_str()) {
+                report.push(
+                    Warning,
+                    at_pattern(),
+                    format!(
+                        "Sample '{}' is not a built-in sample or a project sample file",
+                        pattern.sample_name
+                    ),
+                    false,
+                );
+            }
+            if pattern.steps.len() < longest {
+                report.push(
+                    Warning,
+                    at_pattern(),
+                    format!(
+                        "Pattern has {} steps while others in the segment have {}",
+                        pattern.steps.len(),
+                        longest
+                    ),
+                    false,
+                );
+            }
+            for (index, step) in pattern.steps.iter().enumerate() {
+                if !velocity_in_range(step) {
+                    report.push(
+                        Warning,
+                        IssueLocation::step(&segment.id, &pattern.name, index),
+                        format!("Velocity {} is outside 0.0-1.0", step.velocity),
+                        true,
+                    );
+                }
+            }
+        }
+    }
+
+    // Overlaps are reported on the later segment
+    let mut by_start: Vec<_> = project
+        .timeline
+        .segments
+        .iter()
+        .filter(|segment| segment.start_time.is_finite() && segment.duration.is_finite())
+        .collect();
+    by_start.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+    let mut latest_end: Option<(f64, &str)> = None;
+    for segment in by_start {
+        if let Some((end, earlier_id)) = latest_end {
+            if segment.start_time < end - OVERLAP_EPSILON {
+                report.push(
+                    Warning,
+                    IssueLocation::segment(&segment.id),
+                    format!("Overlaps segment '{}'", earlier_id),
+                    false,
+                );
+            }
+        }
+        let extends_further = match latest_end {
+            Some((end, _)) => segment.end_time() > end,
+            None => true,
+        };
+        if extends_further {
+            latest_end = Some((segment.end_time(), segment.id.as_str()));
+        }
+    }
+
+    if let Some(region) = &project.timeline.loop_region {
+        let valid = region.start.is_finite()
+            && region.end.is_finite()
+            && region.start >= 0.0
+            && region.end > region.start;
+        if !valid {
+            report.push(
+                Error,
+                here(),
+                format!(
+                    "Loop region {}-{} is not a valid range",
+                    region.start, region.end
+                ),
+                true,
+            );
+        }
+    }
+
+    for marker in &project.timeline.markers {
+        if !(marker.time.is_finite() && marker.time >= 0.0) {
+            report.push(
+                Error,
+                IssueLocation::marker(&marker.name),
+                format!("Position {} is before the timeline start", marker.time),
+                true,
+            );
+        }
+    }
+
+    report
+}
+
+/// Apply every safe fix and describe what was changed. Issues that need a decision,
+/// such as unknown samples or overlapping segments, are left alone.
+pub fn repair(project: &mut Project) -> Vec<String> {
+    let corrections: Vec<String> = check(project)
+        .issues
+        .iter()
+        .filter(|issue| issue.fixable)
+        .map(|issue| format!("Fixed {}", issue))
+        .collect();
+    if corrections.is_empty() {
+        return corrections;
+    }
+
+    if project.metadata.name.trim().is_empty() {
+        project.metadata.name = "Untitled Project".to_string();
+    }
+    project.global_bpm = clamp_or(project.global_bpm, 60.0, 300.0, 120.0);
+    project.global_volume = clamp_or(project.global_volume, 0.0, 2.0, 1.0);
+
+    if !time_signature_is_valid(&project.defaults.time_signature) {
+        project.defaults.time_signature = ProjectDefaults::default().time_signature;
+    }
+    project.defaults.pattern_length = project.defaults.pattern_length.clamp(4, 64);
+
+    for segment in &mut project.timeline.segments {
+        if !(segment.start_time.is_finite() && segment.start_time >= 0.0) {
+            segment.start_time = 0.0;
+        }
+
+        // Fixing what the duration is derived from means deriving it again
+        let mut recalculate_duration = !(segment.duration.is_finite() && segment.duration > 0.0);
+        if !time_signature_is_valid(&segment.time_signature) {
+            segment.time_signature = TimeSignature::four_four();
+            recalculate_duration = true;
+        }
+        if !bpm_in_range(segment.bpm) {
+            segment.bpm = clamp_or(segment.bpm, 60.0, 300.0, 120.0);
+            recalculate_duration = true;
+        }
+        if segment.loop_count == 0 {
+            segment.loop_count = 1;
+            recalculate_duration = true;
+        }
+        if recalculate_duration {
+            segment.update_duration();
+        }
+
+        // Step counts are left alone; patterns of different lengths make polymeters
+        for pattern in &mut segment.patterns {
+            for step in &mut pattern.steps {
+                step.velocity = clamp_or(step.velocity, 0.0, 1.0, 1.0);
+            }
+        }
+    }
+
+    // Segments are kept in start order, which moved starts may have broken
+    let timeline = &mut project.timeline;
+    timeline
+        .segments
+        .sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+    if let Some(region) = timeline.loop_region {
+        let start = region.start.max(0.0);
+        timeline.loop_region = if region.end.is_finite() && region.end > start {
+            Some(LoopRegion { start, ..region })
+        } else {
+            None
+        };
+    }
+    for marker in &mut timeline.markers {
+        if !(marker.time.is_finite() && marker.time >= 0.0) {
+            marker.time = 0.0;
+        }
+    }
+    timeline.markers.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+    corrections
+}
+
+// Clamp into range, replacing NaN with `fallback`
+fn clamp_or(value: f32, min: f32, max: f32, fallback: f32) -> f32 {
+    if value.is_nan() {
+        fallback
+    } else {
+        value.clamp(min, max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::sequencer::Pattern;
+    use crate::timeline::TimelineSegment;
+
+    fn segment(name: &str, start_time: f64, patterns: Vec<Pattern>) -> TimelineSegment {
+        TimelineSegment::new(
+            name.to_string(),
+            patterns,
+            start_time,
+            1,
+            TimeSignature::four_four(),
+            120.0,
+        )
+    }
+
+    #[test]
+    fn test_report_lists_every_issue_with_location() {
+        let mut project = Project::new("Broken".to_string());
+        project.global_bpm = 500.0;
+
+        let mut kick = Pattern::new("Kick".to_string(), "kick".to_string(), 16);
+        kick.steps[3].velocity = 1.5;
+        let short = Pattern::new("Cowbell".to_string(), "cowbell".to_string(), 8);
+        let first = project
+            .timeline
+            .add_segment(segment("A", 0.0, vec![kick, short]));
+        let second = project.timeline.add_segment(segment("B", 1.0, Vec::new()));
+        project.timeline.segments[1].bpm = 20.0;
+        project.timeline.segments[1].loop_count = 0;
+
+        let report = project.validation_report();
+        assert!(!report.is_valid());
+        assert_eq!(report.errors().count(), 3);
+
+        let find = |message: &str| {
+            report
+                .issues
+                .iter()
+                .find(|issue| issue.message.contains(message))
+                .unwrap_or_else(|| panic!("no issue containing '{}'", message))
+        };
+        assert_eq!(find("Global BPM").location, IssueLocation::project());
+        assert_eq!(
+            find("Velocity 1.5").location,
+            IssueLocation::step(&first, "Kick", 3)
+        );
+        assert_eq!(find("Velocity 1.5").severity, Severity::Warning);
+        assert_eq!(
+            find("Sample 'cowbell'").location,
+            IssueLocation::pattern(&first, "Cowbell")
+        );
+        assert!(!find("Sample 'cowbell'").fixable);
+        assert_eq!(
+            find("has 8 steps").location,
+            IssueLocation::pattern(&first, "Cowbell")
+        );
+        assert!(!find("has 8 steps").fixable);
+        assert_eq!(find("BPM 20").location, IssueLocation::segment(&second));
+        assert_eq!(find("Overlaps").location, IssueLocation::segment(&second));
+        assert_eq!(
+            find("Velocity 1.5").to_string(),
+            format!(
+                "segment '{}', pattern 'Kick', step 4: Velocity 1.5 is outside 0.0-1.0",
+                first
+            )
+        );
+
+        // validate() still fails with the first error, now with its location
+        let error = project.validate().unwrap_err().to_string();
+        assert!(error.starts_with("project: Global BPM 500"), "{}", error);
+
+        println!("✅ Validation report test passed");
+    }
+
+    #[test]
+    fn test_project_sample_files_are_known() {
+        let mut project = Project::new("Custom Kit".to_string());
+        project.timeline.add_segment(segment(
+            "A",
+            0.0,
+            vec![Pattern::new(
+                "Cowbell".to_string(),
+                "cowbell".to_string(),
+                16,
+            )],
+        ));
+        assert_eq!(project.validation_report().warnings().count(), 1);
+
+        project
+            .sample_files
+            .insert("cowbell".to_string(), "cowbell.wav".into());
+        assert!(project.validation_report().is_empty());
+
+        // Synthesized sounds are known too
+        project.sample_files.clear();
+        project.synth_voices.insert(
+            "cowbell".to_string(),
+            crate::audio::synth::DrumVoice::new(crate::audio::synth::DrumModel::Rimshot),
+        );
+        assert!(project.validation_report().is_empty());
+    }
+
+    #[test]
+    fn test_repair_fixes_safe_issues() {
+        let mut project = Project::new("  ".to_string());
+        project.global_volume = f32::NAN;
+        project.defaults.pattern_length = 100;
+
+        let kick = Pattern::new("Kick".to_string(), "kick".to_string(), 16);
+        let short = Pattern::new("Snare".to_string(), "snare".to_string(), 12);
+        project
+            .timeline
+            .add_segment(segment("Overlapping", 0.5, Vec::new()));
+        project
+            .timeline
+            .add_segment(segment("A", 1.0, vec![kick, short]));
+        // Behind the other segment once clamped to the timeline start
+        project.timeline.segments[1].start_time = -2.0;
+        project.timeline.segments[1].bpm = 400.0;
+        project.timeline.segments[1].patterns[0].steps[0].velocity = -0.5;
+        project.timeline.add_marker("Drop", 4.0);
+        project.timeline.markers[0].time = -1.0;
+
+        let report = project.validation_report();
+        assert!(!report.is_valid());
+        assert!(report.is_repairable());
+
+        let corrections = project.repair();
+        assert_eq!(
+            corrections.len(),
+            report.issues.iter().filter(|i| i.fixable).count()
+        );
+        assert!(corrections.iter().all(|c| c.starts_with("Fixed ")));
+
+        assert_eq!(project.metadata.name, "Untitled Project");
+        assert_eq!(project.global_volume, 1.0);
+        assert_eq!(project.defaults.pattern_length, 64);
+        let repaired = &project.timeline.segments[0];
+        assert_eq!(repaired.start_time, 0.0);
+        assert_eq!(repaired.bpm, 300.0);
+        assert_eq!(repaired.pattern_id, "A");
+        // Polymeters survive the repair
+        assert_eq!(repaired.patterns[1].steps.len(), 12);
+        assert_eq!(repaired.patterns[0].steps[0].velocity, 0.0);
+        assert_eq!(project.timeline.markers[0].time, 0.0);
+
+        // Only the overlap and the polymeter are left, and they do not stop the project loading
+        let after = project.validation_report();
+        assert!(after.is_valid());
+        assert_eq!(after.issues.len(), 2);
+        assert!(after
+            .issues
+            .iter()
+            .any(|i| i.message.starts_with("Overlaps")));
+        assert!(after
+            .issues
+            .iter()
+            .any(|i| i.message.contains("has 12 steps")));
+        assert!(project.repair().is_empty());
+
+        println!("✅ Project repair test passed");
+    }
+}
@@ -1,7 +1,8 @@
+use crate::audio::backend::AudioBackend;
 use anyhow::Result;
 use eframe::egui;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Audio settings for the application
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,6 +14,8 @@ pub struct AudioSettings {
     pub device_monitoring_enabled: bool,
     pub auto_fallback_enabled: bool,
     pub last_known_good_device: Option<String>,
+    #[serde(default)]
+    pub backend: AudioBackend,
 }
 
 impl Default for AudioSettings {
@@ -25,6 +28,7 @@ impl Default for AudioSettings {
             device_monitoring_enabled: true,
             auto_fallback_enabled: true,
             last_known_good_device: None,
+            backend: AudioBackend::Device,
         }
     }
 }
@@ -175,6 +179,7 @@ impl UISettings {
 
 /// Keyboard shortcut settings for the application
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct KeyboardSettings {
     // Transport shortcuts
     pub play_pause: String,
@@ -186,9 +191,19 @@ pub struct KeyboardSettings {
     pub timeline_step_forward: String,
     pub timeline_jump_back: String,
     pub timeline_jump_forward: String,
+    pub timeline_prev_marker: String,
+    pub timeline_next_marker: String,
     pub timeline_start: String,
     pub timeline_end: String,
 
+    // Loop region
+    pub loop_set_in: String,
+    pub loop_set_out: String,
+    pub loop_toggle: String,
+
+    // Markers
+    pub marker_add: String,
+
     // Pattern editing
     pub pattern_clear: String,
     pub pattern_select_all: String,
@@ -221,9 +236,19 @@ impl Default for KeyboardSettings {
             timeline_step_forward: "Right".to_string(),
             timeline_jump_back: "Shift+Left".to_string(),
             timeline_jump_forward: "Shift+Right".to_string(),
+            timeline_prev_marker: "Alt+Left".to_string(),
+            timeline_next_marker: "Alt+Right".to_string(),
             timeline_start: "Home".to_string(),
             timeline_end: "End".to_string(),
 
+            // Loop region
+            loop_set_in: "I".to_string(),
+            loop_set_out: "O".to_string(),
+            loop_toggle: "L".to_string(),
+
+            // Markers
+            marker_add: "M".to_string(),
+
             // Pattern editing
             pattern_clear: "Delete".to_string(),
             pattern_select_all: format!("{}+A", primary_modifier),
@@ -249,8 +274,14 @@ impl KeyboardSettings {
             &self.timeline_step_forward,
             &self.timeline_jump_back,
             &self.timeline_jump_forward,
+            &self.timeline_prev_marker,
+            &self.timeline_next_marker,
             &self.timeline_start,
             &self.timeline_end,
+            &self.loop_set_in,
+            &self.loop_set_out,
+            &self.loop_toggle,
+            &self.marker_add,
             &self.pattern_clear,
             &self.pattern_select_all,
             &self.new_project,
@@ -325,8 +356,14 @@ impl KeyboardSettings {
         sanitize_shortcut(&mut self.timeline_step_forward, &defaults.timeline_step_forward, "Timeline Step Forward");
         sanitize_shortcut(&mut self.timeline_jump_back, &defaults.timeline_jump_back, "Timeline Jump Back");
         sanitize_shortcut(&mut self.timeline_jump_forward, &defaults.timeline_jump_forward, "Timeline Jump Forward");
+        sanitize_shortcut(&mut self.timeline_prev_marker, &defaults.timeline_prev_marker, "Previous Marker");
+        sanitize_shortcut(&mut self.timeline_next_marker, &defaults.timeline_next_marker, "Next Marker");
         sanitize_shortcut(&mut self.timeline_start, &defaults.timeline_start, "Timeline Start");
         sanitize_shortcut(&mut self.timeline_end, &defaults.timeline_end, "Timeline End");
+        sanitize_shortcut(&mut self.loop_set_in, &defaults.loop_set_in, "Loop Set In");
+        sanitize_shortcut(&mut self.loop_set_out, &defaults.loop_set_out, "Loop Set Out");
+        sanitize_shortcut(&mut self.loop_toggle, &defaults.loop_toggle, "Loop Toggle");
+        sanitize_shortcut(&mut self.marker_add, &defaults.marker_add, "Add Marker");
         sanitize_shortcut(&mut self.pattern_clear, &defaults.pattern_clear, "Pattern Clear");
         sanitize_shortcut(&mut self.pattern_select_all, &defaults.pattern_select_all, "Pattern Select All");
         sanitize_shortcut(&mut self.new_project, &defaults.new_project, "New Project");
@@ -362,6 +399,9 @@ impl KeyboardSettings {
             "backspace" => egui::Key::Backspace,
             "tab" => egui::Key::Tab,
             "a" => egui::Key::A,
+            "i" => egui::Key::I,
+            "l" => egui::Key::L,
+            "m" => egui::Key::M,
             "n" => egui::Key::N,
             "o" => egui::Key::O,
             "s" => egui::Key::S,
@@ -402,10 +442,12 @@ impl KeyboardSettings {
 
 /// Default settings for new projects
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct DefaultSettings {
     pub default_bpm: f32,
     pub default_time_signature: (u32, u32),
     pub default_pattern_length: usize,
+    pub autosave_interval_secs: u32, // 0 turns autosave off
 }
 
 impl Default for DefaultSettings {
@@ -414,6 +456,7 @@ impl Default for DefaultSettings {
             default_bpm: 120.0,
             default_time_signature: (4, 4),
             default_pattern_length: 16,
+            autosave_interval_secs: 120,
         }
     }
 }
@@ -481,6 +524,20 @@ impl DefaultSettings {
             ));
         }
 
+        // Validate autosave interval (0 disables autosave)
+        if self.autosave_interval_secs != 0 && self.autosave_interval_secs < 10 {
+            return Err(anyhow::anyhow!(
+                "Autosave interval {}s is too short (minimum: 10 seconds, or 0 to disable)",
+                self.autosave_interval_secs
+            ));
+        }
+        if self.autosave_interval_secs > 3600 {
+            return Err(anyhow::anyhow!(
+                "Autosave interval {}s is too long (maximum: 3600 seconds)",
+                self.autosave_interval_secs
+            ));
+        }
+
         Ok(())
     }
 
@@ -558,10 +615,65 @@ impl DefaultSettings {
             self.default_pattern_length = 64;
         }
 
+        // Sanitize autosave interval
+        if self.autosave_interval_secs != 0 && self.autosave_interval_secs < 10 {
+            corrections.push(format!(
+                "Autosave interval {}s is too short, changed to 10s",
+                self.autosave_interval_secs
+            ));
+            self.autosave_interval_secs = 10;
+        } else if self.autosave_interval_secs > 3600 {
+            corrections.push(format!(
+                "Autosave interval {}s is too long, changed to 3600s",
+                self.autosave_interval_secs
+            ));
+            self.autosave_interval_secs = 3600;
+        }
+
         corrections
     }
 }
 
+/// Recently opened projects for File > Open Recent
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecentProjectsSettings {
+    pub paths: Vec<PathBuf>, // Most recent first
+    pub reopen_last_project: bool,
+}
+
+impl RecentProjectsSettings {
+    /// Number of projects remembered
+    pub const MAX_ENTRIES: usize = 10;
+
+    /// Move `path` to the top of the list
+    pub fn add(&mut self, path: &Path) {
+        self.remove(path);
+        self.paths.insert(0, path.to_path_buf());
+        self.paths.truncate(Self::MAX_ENTRIES);
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        self.paths.retain(|recent| recent != path);
+    }
+
+    /// Drop projects whose files no longer exist, returning true if any were removed
+    pub fn prune_missing(&mut self) -> bool {
+        let count = self.paths.len();
+        self.paths.retain(|path| path.is_file());
+        self.paths.len() != count
+    }
+
+    /// Project to reopen at startup, if that option is on
+    pub fn project_to_reopen(&self) -> Option<&Path> {
+        if self.reopen_last_project {
+            self.paths.first().map(PathBuf::as_path)
+        } else {
+            None
+        }
+    }
+}
+
 /// Main application settings structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppSettings {
@@ -569,6 +681,8 @@ pub struct AppSettings {
     pub ui: UISettings,
     pub defaults: DefaultSettings,
     pub keyboard: KeyboardSettings,
+    #[serde(default)]
+    pub recent: RecentProjectsSettings,
 }
 
 impl Default for AppSettings {
@@ -578,6 +692,7 @@ impl Default for AppSettings {
             ui: UISettings::default(),
             defaults: DefaultSettings::default(),
             keyboard: KeyboardSettings::default(),
+            recent: RecentProjectsSettings::default(),
         }
     }
 }
@@ -603,14 +718,20 @@ impl AppSettings {
 
         #[cfg(not(target_arch = "wasm32"))]
         {
-            let config_dir = dirs::config_dir()
-                .ok_or_else(|| anyhow::anyhow!("Could not determine config directory"))?;
+            Ok(Self::get_config_dir()?.join("settings.json"))
+        }
+    }
+
+    /// Get (and create) the application's config directory
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn get_config_dir() -> Result<PathBuf> {
+        let config_dir = dirs::config_dir()
+            .ok_or_else(|| anyhow::anyhow!("Could not determine config directory"))?;
 
-            let app_config_dir = config_dir.join("beatr");
-            std::fs::create_dir_all(&app_config_dir)?;
+        let app_config_dir = config_dir.join("beatr");
+        std::fs::create_dir_all(&app_config_dir)?;
 
-            Ok(app_config_dir.join("settings.json"))
-        }
+        Ok(app_config_dir)
     }
 
     /// Save settings to file
@@ -1072,12 +1193,41 @@ mod tests {
             default_bpm: 500.0,
             default_time_signature: (0, 3),
             default_pattern_length: 100,
+            autosave_interval_secs: 5,
         };
         let corrections = settings.sanitize();
         assert_eq!(settings.default_bpm, 300.0);
         assert_eq!(settings.default_time_signature, (1, 4));
         assert_eq!(settings.default_pattern_length, 64);
-        assert_eq!(corrections.len(), 4); // BPM, numerator, denominator, pattern length
+        assert_eq!(settings.autosave_interval_secs, 10);
+        assert_eq!(corrections.len(), 5); // BPM, numerator, denominator, pattern length, autosave
+    }
+
+    #[test]
+    fn test_autosave_interval_settings() {
+        let mut settings = DefaultSettings::default();
+        assert_eq!(settings.autosave_interval_secs, 120);
+        assert!(settings.validate().is_ok());
+
+        // Zero disables autosave and is valid
+        settings.autosave_interval_secs = 0;
+        assert!(settings.validate().is_ok());
+        assert!(settings.sanitize().is_empty());
+
+        settings.autosave_interval_secs = 5;
+        assert!(settings.validate().is_err());
+
+        settings.autosave_interval_secs = 7200;
+        assert!(settings.validate().is_err());
+        let corrections = settings.sanitize();
+        assert_eq!(settings.autosave_interval_secs, 3600);
+        assert!(corrections[0].contains("too long"));
+
+        // Settings files written before autosave existed get the default interval
+        let legacy = r#"{"default_bpm": 100.0, "default_time_signature": [3, 4], "default_pattern_length": 16}"#;
+        let loaded: DefaultSettings = serde_json::from_str(legacy).unwrap();
+        assert_eq!(loaded.default_bpm, 100.0);
+        assert_eq!(loaded.autosave_interval_secs, 120);
     }
 
     #[test]
@@ -1312,9 +1462,17 @@ mod tests {
         assert!(KeyboardSettings::parse_shortcut(&keyboard.timeline_step_forward).is_some());
         assert!(KeyboardSettings::parse_shortcut(&keyboard.timeline_jump_back).is_some());
         assert!(KeyboardSettings::parse_shortcut(&keyboard.timeline_jump_forward).is_some());
+        assert!(KeyboardSettings::parse_shortcut(&keyboard.timeline_prev_marker).is_some());
+        assert!(KeyboardSettings::parse_shortcut(&keyboard.timeline_next_marker).is_some());
         assert!(KeyboardSettings::parse_shortcut(&keyboard.timeline_start).is_some());
         assert!(KeyboardSettings::parse_shortcut(&keyboard.timeline_end).is_some());
         
+        // Test loop region shortcuts
+        assert!(KeyboardSettings::parse_shortcut(&keyboard.loop_set_in).is_some());
+        assert!(KeyboardSettings::parse_shortcut(&keyboard.loop_set_out).is_some());
+        assert!(KeyboardSettings::parse_shortcut(&keyboard.loop_toggle).is_some());
+        assert!(KeyboardSettings::parse_shortcut(&keyboard.marker_add).is_some());
+        
         // Test pattern editing shortcuts
         assert!(KeyboardSettings::parse_shortcut(&keyboard.pattern_clear).is_some());
         assert!(KeyboardSettings::parse_shortcut(&keyboard.pattern_select_all).is_some());
@@ -1327,6 +1485,22 @@ mod tests {
         assert!(KeyboardSettings::parse_shortcut(&keyboard.open_settings).is_some());
     }
 
+    #[test]
+    fn test_keyboard_settings_missing_fields_use_defaults() {
+        // Settings files written before the loop shortcuts existed must still load
+        let mut value = serde_json::to_value(KeyboardSettings::default()).unwrap();
+        let object = value.as_object_mut().unwrap();
+        object.remove("loop_set_in");
+        object.remove("loop_set_out");
+        object.remove("loop_toggle");
+
+        let keyboard: KeyboardSettings = serde_json::from_value(value).unwrap();
+        assert_eq!(keyboard.loop_set_in, "I");
+        assert_eq!(keyboard.loop_set_out, "O");
+        assert_eq!(keyboard.loop_toggle, "L");
+        assert!(keyboard.validate().is_ok());
+    }
+
     #[test]
     fn test_keyboard_shortcut_conflict_detection() {
         let mut keyboard = KeyboardSettings::default();
@@ -1366,4 +1540,49 @@ mod tests {
             assert!(KeyboardSettings::matches_shortcut(&keyboard.new_project, egui::Key::N, &ctrl_modifiers));
         }
     }
+
+    #[test]
+    fn test_recent_projects() {
+        let dir = tempdir().unwrap();
+        let mut recent = RecentProjectsSettings::default();
+        assert!(recent.paths.is_empty());
+
+        let paths: Vec<PathBuf> = (0..12)
+            .map(|i| {
+                let path = dir.path().join(format!("song{}.beatr", i));
+                std::fs::write(&path, "{}").unwrap();
+                path
+            })
+            .collect();
+
+        // Most recent first, capped at MAX_ENTRIES
+        for path in &paths {
+            recent.add(path);
+        }
+        assert_eq!(recent.paths.len(), RecentProjectsSettings::MAX_ENTRIES);
+        assert_eq!(recent.paths[0], paths[11]);
+
+        // Reopening a project moves it to the top without duplicating it
+        recent.add(&paths[5]);
+        assert_eq!(recent.paths[0], paths[5]);
+        assert_eq!(recent.paths.len(), RecentProjectsSettings::MAX_ENTRIES);
+        assert_eq!(recent.paths.iter().filter(|p| **p == paths[5]).count(), 1);
+
+        // Only opted-in users get the last project back
+        assert_eq!(recent.project_to_reopen(), None);
+        recent.reopen_last_project = true;
+        assert_eq!(recent.project_to_reopen(), Some(paths[5].as_path()));
+
+        // Deleted files are pruned
+        std::fs::remove_file(&paths[5]).unwrap();
+        assert!(recent.prune_missing());
+        assert!(!recent.paths.contains(&paths[5]));
+        assert!(!recent.prune_missing());
+
+        // Settings files written before the list existed still load
+        let mut json = serde_json::to_value(AppSettings::default()).unwrap();
+        json.as_object_mut().unwrap().remove("recent");
+        let settings: AppSettings = serde_json::from_value(json).unwrap();
+        assert_eq!(settings.recent, RecentProjectsSettings::default());
+    }
 }
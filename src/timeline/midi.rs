@@ -0,0 +1,372 @@
+use anyhow::Result;
+use std::path::Path;
+
+use super::Timeline;
+
+/// Resolution of the exported file in ticks per quarter note
+pub const TICKS_PER_QUARTER: u32 = 480;
+
+// Steps are 16th notes, matching the audio engine
+const TICKS_PER_STEP: u32 = TICKS_PER_QUARTER / 4;
+const NOTE_LENGTH_TICKS: u32 = TICKS_PER_STEP / 2;
+
+// General MIDI percussion lives on channel 10 (index 9)
+const DRUM_CHANNEL: u8 = 9;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct MidiEvent {
+    pub tick: u32,
+    pub data: Vec<u8>,
+}
+
+/// Map a drum sample to its General MIDI percussion note
+pub fn gm_drum_note(sample_name: &str) -> Option<u8> {
+    match sample_name {
+        "kick" => Some(36),
+        "rimshot" => Some(37),
+        "snare" => Some(38),
+        "clap" => Some(39),
+        "hihat" => Some(42),
+        "tom" => Some(45),
+        "open_hihat" => Some(46),
+        "crash" => Some(49),
+        _ => None,
+    }
+}
+
+/// Encode the timeline as a single-track Standard MIDI File (format 0)
+///
+/// Segments become tempo and time signature changes plus drum notes,
+/// and timeline markers become marker meta events.
+pub fn export_midi(timeline: &Timeline) -> Vec<u8> {
+    let events = collect_events(timeline);
+
+    let mut track = Vec::new();
+    let mut last_tick = 0;
+    for event in &events {
+        write_variable_length(&mut track, event.tick - last_tick);
+        track.extend_from_slice(&event.data);
+        last_tick = event.tick;
+    }
+    // End of track
+    track.extend_from_slice(&[0x00, 0xFF, 0x2F, 0x00]);
+
+    let mut file = Vec::with_capacity(track.len() + 22);
+    file.extend_from_slice(b"MThd");
+    file.extend_from_slice(&6u32.to_be_bytes());
+    file.extend_from_slice(&0u16.to_be_bytes()); // Format 0
+    file.extend_from_slice(&1u16.to_be_bytes()); // One track
+    file.extend_from_slice(&(TICKS_PER_QUARTER as u16).to_be_bytes());
+    file.extend_from_slice(b"MTrk");
+    file.extend_from_slice(&(track.len() as u32).to_be_bytes());
+    file.extend_from_slice(&track);
+    file
+}
+
+/// Write the timeline to `path` as a MIDI file
+pub fn write_midi_file(timeline: &Timeline, path: &Path) -> Result<()> {
+    std::fs::write(path, export_midi(timeline))
+        .map_err(|e| anyhow::anyhow!("Failed to write MIDI file: {}", e))
+}
+
+/// Build the sorted event list for the timeline
+pub(crate) fn collect_events(timeline: &Timeline) -> Vec<MidiEvent> {
+    let tempo_map = TempoMap::from_timeline(timeline);
+
+    // (tick, priority, data): meta events come before note offs, note offs before note ons
+    let mut events: Vec<(u32, u8, Vec<u8>)> = Vec::new();
+
+    events.push((0, 0, meta_event(0x03, b"Beatr")));
+
+    let mut last_bpm = None;
+    let mut last_time_signature = None;
+    for segment in &timeline.segments {
+        let start_tick = tempo_map.seconds_to_ticks(segment.start_time);
+
+        if last_bpm != Some(segment.bpm) {
+            let micros_per_quarter = (60_000_000.0 / segment.bpm as f64).round() as u32;
+            events.push((
+                start_tick,
+                0,
+                meta_event(0x51, &micros_per_quarter.to_be_bytes()[1..]),
+            ));
+            last_bpm = Some(segment.bpm);
+        }
+
+        if last_time_signature != Some(segment.time_signature) {
+            let ts = segment.time_signature;
+            let denominator_power = ts.denominator.trailing_zeros() as u8;
+            events.push((
+                start_tick,
+                0,
+                meta_event(0x58, &[ts.numerator, denominator_power, 24, 8]),
+            ));
+            last_time_signature = Some(ts);
+        }
+
+        // Steps loop for the whole segment by the same rule the audio engine plays them
+        let total_steps = (segment.duration * segment.bpm as f64 / 60.0 * 4.0).round() as usize;
+        for pattern in &segment.patterns {
+            let note = match gm_drum_note(&pattern.sample_name) {
+                Some(note) => note,
+                None => continue,
+            };
+            for step_index in 0..total_steps {
+                let step = match pattern.step_in_loop(step_index) {
+                    Some(step) if step.active => step,
+                    _ => continue,
+                };
+
+                let tick = start_tick + step_index as u32 * TICKS_PER_STEP;
+                let velocity = ((step.velocity * 127.0).round() as u8).clamp(1, 127);
+                events.push((tick, 2, vec![0x90 | DRUM_CHANNEL, note, velocity]));
+                events.push((
+                    tick + NOTE_LENGTH_TICKS,
+                    1,
+                    vec![0x80 | DRUM_CHANNEL, note, 0],
+                ));
+            }
+        }
+    }
+
+    for marker in &timeline.markers {
+        let tick = tempo_map.seconds_to_ticks(marker.time);
+        events.push((tick, 0, meta_event(0x06, marker.name.as_bytes())));
+    }
+
+    // Stable sort keeps insertion order for events with the same tick and priority
+    events.sort_by_key(|(tick, priority, _)| (*tick, *priority));
+    events
+        .into_iter()
+        .map(|(tick, _, data)| MidiEvent { tick, data })
+        .collect()
+}
+
+/// Tempo changes in seconds, used to place events at the right tick
+struct TempoMap {
+    changes: Vec<(f64, f32)>, // (start time in seconds, BPM)
+}
+
+impl TempoMap {
+    fn from_timeline(timeline: &Timeline) -> Self {
+        let mut changes = Vec::new();
+        for segment in &timeline.segments {
+            if changes.last().map(|&(_, bpm)| bpm) != Some(segment.bpm) {
+                changes.push((segment.start_time, segment.bpm));
+            }
+        }
+
+        // Anything before the first segment runs at the first segment's tempo
+        match changes.first_mut() {
+            Some(first) => first.0 = 0.0,
+            None => changes.push((0.0, 120.0)),
+        }
+
+        TempoMap { changes }
+    }
+
+    fn seconds_to_ticks(&self, seconds: f64) -> u32 {
+        let mut ticks = 0.0;
+        for (index, &(start, bpm)) in self.changes.iter().enumerate() {
+            if seconds <= start {
+                break;
+            }
+            let end = self
+                .changes
+                .get(index + 1)
+                .map(|&(next_start, _)| next_start.min(seconds))
+                .unwrap_or(seconds);
+            ticks += (end - start) * bpm as f64 / 60.0 * TICKS_PER_QUARTER as f64;
+        }
+        ticks.round() as u32
+    }
+}
+
+fn meta_event(kind: u8, payload: &[u8]) -> Vec<u8> {
+    let mut data = vec![0xFF, kind];
+    write_variable_length(&mut data, payload.len() as u32);
+    data.extend_from_slice(payload);
+    data
+}
+
+fn write_variable_length(output: &mut Vec<u8>, value: u32) {
+    let mut buffer = [0u8; 5];
+    let mut index = buffer.len() - 1;
+    let mut remaining = value;
+
+    buffer[index] = (remaining & 0x7F) as u8;
+    remaining >>= 7;
+    while remaining > 0 {
+        index -= 1;
+        buffer[index] = (remaining & 0x7F) as u8 | 0x80;
+        remaining >>= 7;
+    }
+
+    output.extend_from_slice(&buffer[index..]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::{sequencer::Pattern, TimeSignature};
+    use crate::timeline::TimelineSegment;
+
+    fn segment_with_kick_on_beats(start_time: f64, bpm: f32) -> TimelineSegment {
+        let mut kick = Pattern::new("Kick".to_string(), "kick".to_string(), 16);
+        for step in [0, 4, 8, 12] {
+            kick.toggle_step(step);
+        }
+        TimelineSegment::new(
+            "Beat".to_string(),
+            vec![kick],
+            start_time,
+            1,
+            TimeSignature::four_four(),
+            bpm,
+        )
+    }
+
+    #[test]
+    fn test_variable_length_encoding() {
+        let encode = |value| {
+            let mut output = Vec::new();
+            write_variable_length(&mut output, value);
+            output
+        };
+
+        assert_eq!(encode(0), vec![0x00]);
+        assert_eq!(encode(0x7F), vec![0x7F]);
+        assert_eq!(encode(0x80), vec![0x81, 0x00]);
+        assert_eq!(encode(1920), vec![0x8F, 0x00]);
+        assert_eq!(encode(0x0FFF_FFFF), vec![0xFF, 0xFF, 0xFF, 0x7F]);
+    }
+
+    #[test]
+    fn test_midi_file_header() {
+        let timeline = Timeline::new();
+        let bytes = export_midi(&timeline);
+
+        assert_eq!(&bytes[0..4], b"MThd");
+        assert_eq!(&bytes[8..10], &[0, 0], "Format 0");
+        assert_eq!(&bytes[10..12], &[0, 1], "Single track");
+        assert_eq!(&bytes[12..14], &(TICKS_PER_QUARTER as u16).to_be_bytes());
+        assert_eq!(&bytes[14..18], b"MTrk");
+
+        let track_length = u32::from_be_bytes([bytes[18], bytes[19], bytes[20], bytes[21]]);
+        assert_eq!(track_length as usize, bytes.len() - 22);
+        assert_eq!(
+            &bytes[bytes.len() - 3..],
+            &[0xFF, 0x2F, 0x00],
+            "End of track"
+        );
+    }
+
+    #[test]
+    fn test_markers_exported_as_marker_events() {
+        let mut timeline = Timeline::new();
+        timeline.add_segment(segment_with_kick_on_beats(0.0, 120.0));
+        timeline.add_marker("Intro", 0.0);
+        timeline.add_marker("Chorus", 1.0);
+
+        let markers: Vec<(u32, Vec<u8>)> = collect_events(&timeline)
+            .into_iter()
+            .filter(|e| e.data[0] == 0xFF && e.data[1] == 0x06)
+            .map(|e| (e.tick, e.data[3..].to_vec()))
+            .collect();
+
+        // 1 second at 120 BPM is two quarter notes
+        assert_eq!(
+            markers,
+            vec![
+                (0, b"Intro".to_vec()),
+                (2 * TICKS_PER_QUARTER, b"Chorus".to_vec())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tempo_changes_place_events_correctly() {
+        let mut timeline = Timeline::new();
+        // One bar at 120 BPM (2 s) followed by one bar at 60 BPM (4 s)
+        timeline.add_segment(segment_with_kick_on_beats(0.0, 120.0));
+        timeline.add_segment(segment_with_kick_on_beats(2.0, 60.0));
+        // One second into the 60 BPM bar is one quarter note past its start
+        timeline.add_marker("Drop", 3.0);
+
+        let events = collect_events(&timeline);
+
+        let tempos: Vec<(u32, Vec<u8>)> = events
+            .iter()
+            .filter(|e| e.data[..2] == [0xFF, 0x51])
+            .map(|e| (e.tick, e.data[3..].to_vec()))
+            .collect();
+        assert_eq!(
+            tempos,
+            vec![
+                (0, vec![0x07, 0xA1, 0x20]),                     // 500000 us per quarter
+                (4 * TICKS_PER_QUARTER, vec![0x0F, 0x42, 0x40])  // 1000000 us per quarter
+            ]
+        );
+
+        let drop_tick = events
+            .iter()
+            .find(|e| e.data[..2] == [0xFF, 0x06])
+            .map(|e| e.tick);
+        assert_eq!(drop_tick, Some(5 * TICKS_PER_QUARTER));
+
+        // Four kicks per bar, each with a note on and a note off
+        let note_ons: Vec<u32> = events
+            .iter()
+            .filter(|e| e.data[0] == 0x90 | DRUM_CHANNEL)
+            .map(|e| e.tick)
+            .collect();
+        let expected: Vec<u32> = (0..8).map(|beat| beat * TICKS_PER_QUARTER).collect();
+        assert_eq!(note_ons, expected);
+        assert!(events
+            .iter()
+            .filter(|e| e.data[0] == 0x90 | DRUM_CHANNEL)
+            .all(|e| e.data[1] == 36));
+    }
+
+    #[test]
+    fn test_patterns_loop_like_playback() {
+        // Two bars at 120 BPM: like the engine, every pattern loops over its own steps
+        let mut short = Pattern::new("Short".to_string(), "kick".to_string(), 8);
+        short.toggle_step(0);
+        short.toggle_step(4);
+        let mut long = Pattern::new("Long".to_string(), "snare".to_string(), 24);
+        long.toggle_step(2);
+        long.toggle_step(20);
+        let mut timeline = Timeline::new();
+        timeline.add_segment(TimelineSegment::new(
+            "Odd".to_string(),
+            vec![short, long],
+            0.0,
+            2,
+            TimeSignature::four_four(),
+            120.0,
+        ));
+
+        let note_ons = |note: u8| -> Vec<u32> {
+            collect_events(&timeline)
+                .into_iter()
+                .filter(|e| e.data[0] == 0x90 | DRUM_CHANNEL && e.data[1] == note)
+                .map(|e| e.tick / TICKS_PER_STEP)
+                .collect()
+        };
+        // The short pattern repeats every half bar, the long one every bar and a half
+        assert_eq!(note_ons(36), vec![0, 4, 8, 12, 16, 20, 24, 28]);
+        assert_eq!(note_ons(38), vec![2, 20, 26]);
+    }
+
+    #[test]
+    fn test_events_sorted_by_tick() {
+        let mut timeline = Timeline::new();
+        timeline.add_segment(segment_with_kick_on_beats(0.0, 93.0));
+        timeline.add_segment(segment_with_kick_on_beats(5.0, 140.0));
+        timeline.add_marker("Verse", 5.0);
+
+        let events = collect_events(&timeline);
+        assert!(events.windows(2).all(|pair| pair[0].tick <= pair[1].tick));
+    }
+}
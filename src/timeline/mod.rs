@@ -1,15 +1,32 @@
+pub mod midi;
+
 use crate::audio::{sequencer::Pattern, TimeSignature};
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicUsize, Ordering};
 
 // Simple ID generator for timeline segments
 static SEGMENT_ID_COUNTER: AtomicUsize = AtomicUsize::new(0);
+static MARKER_ID_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
 fn generate_segment_id() -> String {
     let id = SEGMENT_ID_COUNTER.fetch_add(1, Ordering::SeqCst);
     format!("segment_{}", id)
 }
 
+fn generate_marker_id() -> String {
+    let id = MARKER_ID_COUNTER.fetch_add(1, Ordering::SeqCst);
+    format!("marker_{}", id)
+}
+
+// Positions closer than this count as sitting on a marker when jumping between markers
+const MARKER_TIME_EPSILON: f64 = 1e-3;
+
+// Segments starting this close to an edit point count as coming after it
+const RIPPLE_TIME_EPSILON: f64 = 1e-6;
+
+// Bar length used when inserting time into an empty timeline
+const DEFAULT_BPM: f32 = 120.0;
+
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum PlaybackState {
     Stopped,
@@ -17,7 +34,7 @@ pub enum PlaybackState {
     Paused,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TimelineSegment {
     pub id: String,
     pub start_time: f64,        // Seconds from timeline start
@@ -68,6 +85,11 @@ impl TimelineSegment {
         time >= self.start_time && time < self.end_time()
     }
 
+    /// Length of one bar at this segment's tempo and time signature
+    pub fn bar_duration(&self) -> f64 {
+        self.time_signature.numerator as f64 * 60.0 / self.bpm as f64
+    }
+
     pub fn update_duration(&mut self) {
         // Recalculate duration when loop count (bars), time signature, or BPM changes
         let beats_per_bar = self.time_signature.numerator as f64;
@@ -90,6 +112,101 @@ impl TimelineSegment {
         self.time_signature = time_signature;
         self.update_duration();
     }
+
+    /// Steps played from the segment start up to timeline `position`, or None outside
+    /// this segment. Steps are sixteenth notes, and each pattern wraps the count at its
+    /// own length, as in the audio engine.
+    pub fn steps_elapsed_at(&self, position: f64) -> Option<usize> {
+        if !self.contains_time(position) {
+            return None;
+        }
+        let steps_per_second = self.bpm as f64 / 60.0 * 4.0;
+        Some(((position - self.start_time) * steps_per_second) as usize)
+    }
+
+    /// Replace the patterns playing the same samples as `patterns` and append the rest
+    pub fn merge_patterns(&mut self, patterns: Vec<Pattern>) {
+        for pattern in patterns {
+            match self
+                .patterns
+                .iter_mut()
+                .find(|existing| existing.sample_name == pattern.sample_name)
+            {
+                Some(existing) => *existing = pattern,
+                None => self.patterns.push(pattern),
+            }
+        }
+    }
+}
+
+/// Named section preset offered when adding markers, with its display color
+pub const MARKER_PRESETS: [(&str, [u8; 3]); 6] = [
+    ("Intro", [90, 170, 255]),
+    ("Verse", [100, 210, 120]),
+    ("Chorus", [255, 190, 60]),
+    ("Drop", [255, 90, 90]),
+    ("Bridge", [190, 120, 255]),
+    ("Outro", [160, 160, 160]),
+];
+
+/// Named, colored position on the timeline marking the start of a section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Marker {
+    pub id: String,
+    pub name: String,
+    pub time: f64,      // Seconds from timeline start
+    pub color: [u8; 3], // RGB
+}
+
+impl Marker {
+    pub fn new(name: String, time: f64, color: [u8; 3]) -> Self {
+        Marker {
+            id: generate_marker_id(),
+            name,
+            time: time.max(0.0),
+            color,
+        }
+    }
+
+    /// Preset color for well-known section names, cycling through the palette otherwise
+    pub fn color_for_name(name: &str, index: usize) -> [u8; 3] {
+        MARKER_PRESETS
+            .iter()
+            .find(|(preset, _)| preset.eq_ignore_ascii_case(name.trim()))
+            .map(|(_, color)| *color)
+            .unwrap_or(MARKER_PRESETS[index % MARKER_PRESETS.len()].1)
+    }
+}
+
+/// In/out region that playback repeats instead of stopping at the end of the timeline
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LoopRegion {
+    pub start: f64, // Loop in point in seconds
+    pub end: f64,   // Loop out point in seconds
+    pub enabled: bool,
+}
+
+impl LoopRegion {
+    /// Shortest loop region allowed, in seconds
+    pub const MIN_LENGTH: f64 = 0.05;
+
+    pub fn new(start: f64, end: f64) -> Self {
+        let (start, end) = if start <= end {
+            (start, end)
+        } else {
+            (end, start)
+        };
+        let start = start.max(0.0);
+        LoopRegion {
+            start,
+            end: end.max(start + Self::MIN_LENGTH),
+            enabled: true,
+        }
+    }
+
+    pub fn length(&self) -> f64 {
+        self.end - self.start
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -97,6 +214,10 @@ pub struct Timeline {
     pub segments: Vec<TimelineSegment>,
     pub current_position: f64, // Current playback position in seconds
     pub playback_state: PlaybackState,
+    #[serde(default)]
+    pub loop_region: Option<LoopRegion>,
+    #[serde(default)]
+    pub markers: Vec<Marker>, // Kept sorted by time
 }
 
 impl Timeline {
@@ -105,9 +226,18 @@ impl Timeline {
             segments: Vec::new(),
             current_position: 0.0,
             playback_state: PlaybackState::Stopped,
+            loop_region: None,
+            markers: Vec::new(),
         }
     }
 
+    /// True when both timelines hold the same arrangement; the playhead is not compared
+    pub fn same_content(&self, other: &Timeline) -> bool {
+        self.segments == other.segments
+            && self.loop_region == other.loop_region
+            && self.markers == other.markers
+    }
+
     pub fn total_duration(&self) -> f64 {
         self.segments
             .iter()
@@ -128,14 +258,179 @@ impl Timeline {
         id
     }
 
-    pub fn remove_segment(&mut self, segment_id: &str) -> Option<TimelineSegment> {
+    /// Remove a segment; with `ripple` everything after it moves back to close the gap
+    pub fn remove_segment(&mut self, segment_id: &str, ripple: bool) -> Option<TimelineSegment> {
         if let Some(index) = self.segments.iter().position(|s| s.id == segment_id) {
-            Some(self.segments.remove(index))
+            let removed = self.segments.remove(index);
+            if ripple {
+                self.shift_after(removed.end_time(), -removed.duration);
+            }
+            Some(removed)
         } else {
             None
         }
     }
 
+    /// Apply `edit` to a segment; with `ripple` later material follows the segment's new end
+    pub fn edit_segment<F>(&mut self, segment_id: &str, ripple: bool, edit: F) -> bool
+    where
+        F: FnOnce(&mut TimelineSegment),
+    {
+        let segment = match self.get_segment_mut(segment_id) {
+            Some(segment) => segment,
+            None => return false,
+        };
+
+        let old_end = segment.end_time();
+        edit(segment);
+        let new_end = segment.end_time();
+
+        if ripple && new_end != old_end {
+            self.shift_after(old_end, new_end - old_end);
+        }
+        true
+    }
+
+    /// Segment whose tempo and time signature apply at `position`: the last one starting
+    /// at or before it, or the first segment for positions before any segment
+    pub fn tempo_segment_at(&self, position: f64) -> Option<&TimelineSegment> {
+        self.segments
+            .iter()
+            .rev()
+            .find(|s| s.start_time <= position + RIPPLE_TIME_EPSILON)
+            .or(self.segments.first())
+    }
+
+    /// Length of `bars` bars using the tempo and time signature in effect at `position`
+    pub fn bars_duration_at(&self, position: f64, bars: usize) -> f64 {
+        let (beats_per_bar, bpm) = self
+            .tempo_segment_at(position)
+            .map(|s| (s.time_signature.numerator as f64, s.bpm))
+            .unwrap_or((TimeSignature::four_four().numerator as f64, DEFAULT_BPM));
+
+        beats_per_bar * bars as f64 * 60.0 / bpm as f64
+    }
+
+    /// Open a gap of `bars` bars at `position`, splitting the segment under it if needed.
+    /// Inside a segment the gap opens at the nearest bar line. Returns the inserted
+    /// duration in seconds.
+    pub fn insert_bars(&mut self, position: f64, bars: usize) -> f64 {
+        let position = self.snap_to_bar_line(position.max(0.0));
+        let duration = self.bars_duration_at(position, bars);
+        if duration <= 0.0 {
+            return 0.0;
+        }
+
+        self.split_segments_at(position);
+        self.shift_after(position, duration);
+        duration
+    }
+
+    /// Cut the time between `start` and `end` out of the timeline. Range edges inside a
+    /// segment move to its nearest bar line, so segments are trimmed by whole bars.
+    /// Everything inside is removed and later material moves back.
+    pub fn delete_time_range(&mut self, start: f64, end: f64) {
+        let (start, end) = if start <= end {
+            (start.max(0.0), end.max(0.0))
+        } else {
+            (end.max(0.0), start.max(0.0))
+        };
+        let (start, end) = (self.snap_to_bar_line(start), self.snap_to_bar_line(end));
+        let length = end - start;
+        if length <= RIPPLE_TIME_EPSILON {
+            return;
+        }
+
+        self.split_segments_at(start);
+        self.split_segments_at(end);
+
+        self.segments.retain(|s| {
+            s.start_time < start - RIPPLE_TIME_EPSILON || s.start_time >= end - RIPPLE_TIME_EPSILON
+        });
+        self.markers.retain(|m| m.time < start || m.time >= end);
+
+        let collapse = |time: f64| {
+            if time >= end {
+                time - length
+            } else {
+                time.min(start)
+            }
+        };
+        self.remap_times(collapse);
+        self.current_position = collapse(self.current_position);
+    }
+
+    // Nearest bar line of the segment playing at `time`, so splits keep every piece a
+    // whole number of bars starting on the pattern's first step. Unchanged between segments.
+    fn snap_to_bar_line(&self, time: f64) -> f64 {
+        match self.segment_at(time) {
+            Some(segment) => {
+                let bar = segment.bar_duration();
+                let bars = ((time - segment.start_time) / bar).round();
+                segment.start_time + bars * bar
+            }
+            None => time,
+        }
+    }
+
+    // Split any segment that straddles `time` so edits can treat it as a boundary
+    fn split_segments_at(&mut self, time: f64) {
+        let straddling: Vec<String> = self
+            .segments
+            .iter()
+            .filter(|s| {
+                time > s.start_time + RIPPLE_TIME_EPSILON
+                    && time < s.end_time() - RIPPLE_TIME_EPSILON
+            })
+            .map(|s| s.id.clone())
+            .collect();
+
+        for segment_id in straddling {
+            self.split_segment(&segment_id, time);
+        }
+    }
+
+    // Move every segment, marker and loop point at or after `time` by `delta`
+    fn shift_after(&mut self, time: f64, delta: f64) {
+        self.remap_times(|t| {
+            if t >= time - RIPPLE_TIME_EPSILON {
+                (t + delta).max(0.0)
+            } else {
+                t
+            }
+        });
+    }
+
+    fn remap_times<F>(&mut self, map: F)
+    where
+        F: Fn(f64) -> f64,
+    {
+        for segment in &mut self.segments {
+            segment.start_time = map(segment.start_time);
+        }
+        self.segments
+            .sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap());
+
+        for marker in &mut self.markers {
+            marker.time = map(marker.time);
+        }
+        self.markers
+            .sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+
+        if let Some(region) = self.loop_region {
+            let (start, end) = (map(region.start), map(region.end));
+            self.loop_region = if end - start >= LoopRegion::MIN_LENGTH {
+                Some(LoopRegion {
+                    start,
+                    end,
+                    enabled: region.enabled,
+                })
+            } else {
+                None
+            };
+        }
+    }
+
     pub fn get_segment(&self, segment_id: &str) -> Option<&TimelineSegment> {
         self.segments.iter().find(|s| s.id == segment_id)
     }
@@ -145,9 +440,26 @@ impl Timeline {
     }
 
     pub fn get_current_segment(&self) -> Option<&TimelineSegment> {
+        self.segment_at(self.current_position)
+    }
+
+    pub fn segment_at(&self, time: f64) -> Option<&TimelineSegment> {
         self.segments
             .iter()
-            .find(|segment| segment.contains_time(self.current_position))
+            .find(|segment| segment.contains_time(time))
+    }
+
+    /// Earliest point after `time` where what's playing can change: a segment
+    /// edge, the loop out point or the end of the timeline. Infinite if none.
+    pub fn next_boundary(&self, time: f64) -> f64 {
+        let loop_end = self.playing_loop().map(|region| region.end);
+
+        self.segments
+            .iter()
+            .flat_map(|segment| [segment.start_time, segment.end_time()])
+            .chain(loop_end)
+            .filter(|&boundary| boundary > time)
+            .fold(f64::INFINITY, f64::min)
     }
 
     pub fn move_segment(&mut self, segment_id: &str, new_start_time: f64) -> bool {
@@ -163,6 +475,57 @@ impl Timeline {
         }
     }
 
+    /// Shift several segments by the same amount, keeping their relative spacing.
+    /// The shift is limited so no segment starts before zero; returns the shift applied.
+    pub fn move_segments(&mut self, segment_ids: &[String], delta: f64) -> f64 {
+        let earliest_start = self
+            .segments
+            .iter()
+            .filter(|s| segment_ids.contains(&s.id))
+            .map(|s| s.start_time)
+            .fold(f64::INFINITY, f64::min);
+        if !earliest_start.is_finite() {
+            return 0.0;
+        }
+
+        let delta = delta.max(-earliest_start);
+        for segment in self
+            .segments
+            .iter_mut()
+            .filter(|s| segment_ids.contains(&s.id))
+        {
+            segment.start_time += delta;
+        }
+
+        self.segments
+            .sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap());
+        delta
+    }
+
+    /// Copy a group of segments right after the group's end, keeping their arrangement.
+    /// Returns the new segment ids in the same order as `segment_ids`.
+    pub fn duplicate_segments(&mut self, segment_ids: &[String], gap: f64) -> Vec<String> {
+        let (group_start, group_end) = self
+            .segments
+            .iter()
+            .filter(|s| segment_ids.contains(&s.id))
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(start, end), s| {
+                (start.min(s.start_time), end.max(s.end_time()))
+            });
+        if !group_start.is_finite() {
+            return Vec::new();
+        }
+
+        let offset = group_end - group_start + gap;
+        segment_ids
+            .iter()
+            .filter_map(|id| {
+                let start_time = self.get_segment(id)?.start_time;
+                self.duplicate_segment(id, start_time + offset)
+            })
+            .collect()
+    }
+
     pub fn duplicate_segment(&mut self, segment_id: &str, new_start_time: f64) -> Option<String> {
         if let Some(original) = self.get_segment(segment_id) {
             let mut new_segment = original.clone();
@@ -234,9 +597,10 @@ impl Timeline {
     }
 
     /// Update BPM for all segments and recalculate their durations
-    pub fn set_global_bpm(&mut self, bpm: f32) {
-        for segment in &mut self.segments {
-            segment.set_bpm(bpm);
+    pub fn set_global_bpm(&mut self, bpm: f32, ripple: bool) {
+        let segment_ids: Vec<String> = self.segments.iter().map(|s| s.id.clone()).collect();
+        for segment_id in segment_ids {
+            self.edit_segment(&segment_id, ripple, |segment| segment.set_bpm(bpm));
         }
     }
 
@@ -250,10 +614,137 @@ impl Timeline {
         }
     }
 
+    /// Set the loop region; the points may be given in either order
+    pub fn set_loop_region(&mut self, start: f64, end: f64) {
+        self.loop_region = Some(LoopRegion::new(start, end));
+    }
+
+    /// Move the loop in point, creating a region up to the end of the timeline if needed
+    pub fn set_loop_in(&mut self, position: f64) {
+        let end = match self.loop_region {
+            Some(region) if region.end > position => region.end,
+            _ => self.total_duration().max(position + LoopRegion::MIN_LENGTH),
+        };
+        self.set_loop_region(position, end);
+    }
+
+    /// Move the loop out point, creating a region from the start of the timeline if needed
+    pub fn set_loop_out(&mut self, position: f64) {
+        let start = match self.loop_region {
+            Some(region) if region.start < position => region.start,
+            _ => 0.0,
+        };
+        self.set_loop_region(start, position);
+    }
+
+    pub fn clear_loop_region(&mut self) {
+        self.loop_region = None;
+    }
+
+    /// Enable or disable looping; without a region the whole timeline is looped
+    pub fn toggle_loop(&mut self) {
+        match self.loop_region.as_mut() {
+            Some(region) => region.enabled = !region.enabled,
+            None => {
+                let total = self.total_duration();
+                if total > 0.0 {
+                    self.set_loop_region(0.0, total);
+                }
+            }
+        }
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.loop_region.map(|r| r.enabled).unwrap_or(false)
+    }
+
+    /// The enabled loop region as played: its out point is cut back to the end of
+    /// the timeline, and a region left entirely past the end does not loop.
+    fn playing_loop(&self) -> Option<LoopRegion> {
+        let region = self.loop_region.filter(|r| r.enabled)?;
+        let end = region.end.min(self.total_duration());
+        (end - region.start >= LoopRegion::MIN_LENGTH).then_some(LoopRegion { end, ..region })
+    }
+
+    /// Add a marker at `time`, picking its color from the section presets
+    pub fn add_marker(&mut self, name: &str, time: f64) -> String {
+        let color = Marker::color_for_name(name, self.markers.len());
+        self.insert_marker(Marker::new(name.to_string(), time, color))
+    }
+
+    /// Insert a marker keeping the list in chronological order. A marker whose id is
+    /// already taken, such as one loaded from a project saved by an earlier run, gets a
+    /// new id. Returns the id the marker ends up with.
+    pub fn insert_marker(&mut self, mut marker: Marker) -> String {
+        while self.markers.iter().any(|m| m.id == marker.id) {
+            marker.id = generate_marker_id();
+        }
+        let id = marker.id.clone();
+        let insert_index = self.markers.partition_point(|m| m.time <= marker.time);
+        self.markers.insert(insert_index, marker);
+        id
+    }
+
+    pub fn remove_marker(&mut self, marker_id: &str) -> Option<Marker> {
+        let index = self.markers.iter().position(|m| m.id == marker_id)?;
+        Some(self.markers.remove(index))
+    }
+
+    pub fn get_marker(&self, marker_id: &str) -> Option<&Marker> {
+        self.markers.iter().find(|m| m.id == marker_id)
+    }
+
+    pub fn rename_marker(&mut self, marker_id: &str, name: &str) -> bool {
+        match self.markers.iter_mut().find(|m| m.id == marker_id) {
+            Some(marker) => {
+                marker.name = name.to_string();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn move_marker(&mut self, marker_id: &str, time: f64) -> bool {
+        match self.remove_marker(marker_id) {
+            Some(mut marker) => {
+                marker.time = time.max(0.0);
+                self.insert_marker(marker);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// First marker strictly after `position`
+    pub fn next_marker(&self, position: f64) -> Option<&Marker> {
+        self.markers
+            .iter()
+            .find(|m| m.time > position + MARKER_TIME_EPSILON)
+    }
+
+    /// Last marker strictly before `position`
+    pub fn previous_marker(&self, position: f64) -> Option<&Marker> {
+        self.markers
+            .iter()
+            .rev()
+            .find(|m| m.time < position - MARKER_TIME_EPSILON)
+    }
+
     pub fn advance_position(&mut self, delta_time: f64) -> bool {
         if self.playback_state == PlaybackState::Playing {
+            let previous_position = self.current_position;
             self.current_position += delta_time;
 
+            // Wrap back to the loop in point once the playhead crosses the out point.
+            // Playback that starts after the region is left alone.
+            if let Some(region) = self.playing_loop() {
+                if previous_position < region.end && self.current_position >= region.end {
+                    let overshoot = (self.current_position - region.end) % region.length();
+                    self.current_position = region.start + overshoot;
+                    return true;
+                }
+            }
+
             // Check if we've reached the end
             if self.current_position >= self.total_duration() {
                 self.stop();
@@ -269,6 +760,23 @@ impl Timeline {
         self.playback_state == PlaybackState::Playing
     }
 
+    /// Position being heard while audio takes `output_latency` seconds to reach the
+    /// speakers after the playhead passes it. Outside playback nothing is in flight.
+    pub fn audible_position(&self, output_latency: f64) -> f64 {
+        if !self.is_playing() {
+            return self.current_position;
+        }
+
+        let position = self.current_position - output_latency;
+        match self.playing_loop() {
+            // Just after wrapping, the end of the loop is still playing
+            Some(region) if self.current_position >= region.start && position < region.start => {
+                (position + region.length()).max(region.start)
+            }
+            _ => position.max(0.0),
+        }
+    }
+
     pub fn is_empty(&self) -> bool {
         self.segments.is_empty()
     }
@@ -357,6 +865,68 @@ mod tests {
         assert!((segment.duration - expected_duration).abs() < 0.001);
     }
 
+    #[test]
+    fn test_timeline_segment_merge_patterns() {
+        let mut segment = TimelineSegment::new(
+            "test".to_string(),
+            vec![
+                Pattern::new("Kick".to_string(), "kick".to_string(), 16),
+                Pattern::new("Snare".to_string(), "snare".to_string(), 16),
+            ],
+            0.0,
+            1,
+            TimeSignature::four_four(),
+            120.0,
+        );
+
+        let mut kick = Pattern::new("Big Kick".to_string(), "kick".to_string(), 8);
+        kick.toggle_step(0);
+        let clap = Pattern::new("clap".to_string(), "clap".to_string(), 16);
+        segment.merge_patterns(vec![kick, clap]);
+
+        assert_eq!(segment.patterns.len(), 3);
+        assert_eq!(segment.patterns[0].name, "Big Kick");
+        assert_eq!(segment.patterns[0].steps.len(), 8);
+        assert!(segment.patterns[0].steps[0].active);
+        assert_eq!(segment.patterns[1].sample_name, "snare");
+        assert_eq!(segment.patterns[2].sample_name, "clap");
+    }
+
+    #[test]
+    fn test_audible_position_and_step() {
+        let mut timeline = Timeline::new();
+        let segment = TimelineSegment::new(
+            "test".to_string(),
+            vec![Pattern::new("Kick".to_string(), "kick".to_string(), 16)],
+            2.0,
+            4,
+            TimeSignature::four_four(),
+            120.0,
+        );
+        let segment_id = timeline.add_segment(segment);
+
+        // Stopped or paused, there is nothing to compensate
+        timeline.seek(3.0);
+        assert_eq!(timeline.audible_position(0.1), 3.0);
+
+        timeline.play();
+        timeline.seek(3.0);
+        assert!((timeline.audible_position(0.1) - 2.9).abs() < 1e-9);
+        assert_eq!(timeline.audible_position(5.0), 0.0);
+
+        // Right after wrapping around the loop, the end of the loop is heard
+        timeline.set_loop_region(2.0, 4.0);
+        timeline.seek(2.05);
+        assert!((timeline.audible_position(0.1) - 3.95).abs() < 1e-9);
+
+        // At 120 BPM a sixteenth note lasts 0.125 seconds
+        let segment = timeline.get_segment(&segment_id).unwrap();
+        assert_eq!(segment.steps_elapsed_at(1.9), None);
+        assert_eq!(segment.steps_elapsed_at(2.0), Some(0));
+        assert_eq!(segment.steps_elapsed_at(2.3), Some(2));
+        assert_eq!(segment.steps_elapsed_at(4.0), Some(16));
+    }
+
     #[test]
     fn test_timeline_basic_operations() {
         let mut timeline = Timeline::new();
@@ -417,7 +987,7 @@ mod tests {
         assert_eq!(current.unwrap().id, id2);
 
         // Test remove
-        let removed = timeline.remove_segment(&id1);
+        let removed = timeline.remove_segment(&id1, false);
         assert!(removed.is_some());
         assert_eq!(timeline.segments.len(), 1);
         assert!(timeline.get_segment(&id1).is_none());
@@ -516,6 +1086,415 @@ mod tests {
         assert!(!timeline.is_playing()); // Should stop automatically
     }
 
+    #[test]
+    fn test_loop_region_playback_wraps() {
+        let mut timeline = Timeline::new();
+
+        // 4 bars of 4/4 at 120 BPM = 8 seconds
+        let pattern = Pattern::new("pattern1".to_string(), "kick".to_string(), 16);
+        let segment = TimelineSegment::new(
+            "pattern1".to_string(),
+            vec![pattern],
+            0.0,
+            4,
+            TimeSignature::four_four(),
+            120.0,
+        );
+        timeline.add_segment(segment);
+
+        timeline.set_loop_region(2.0, 4.0);
+        assert!(timeline.is_looping());
+
+        timeline.current_position = 3.5;
+        timeline.play();
+
+        // Crossing the out point wraps back to the in point, carrying the overshoot
+        assert!(timeline.advance_position(1.0));
+        assert!(timeline.is_playing());
+        assert!((timeline.current_position - 2.5).abs() < 1e-9);
+
+        // Looping never stops, even over many passes
+        for _ in 0..100 {
+            assert!(timeline.advance_position(0.7));
+        }
+        assert!(timeline.is_playing());
+        assert!(timeline.current_position >= 2.0 && timeline.current_position < 4.0);
+
+        // Playback started after the region runs to the end and stops as usual
+        timeline.current_position = 7.5;
+        assert!(!timeline.advance_position(1.0));
+        assert!(!timeline.is_playing());
+
+        println!("✅ Loop region playback wrap test passed");
+    }
+
+    #[test]
+    fn test_loop_region_past_the_end_wraps_at_the_end() {
+        let mut timeline = Timeline::new();
+
+        // 4 bars of 4/4 at 120 BPM = 8 seconds
+        let pattern = Pattern::new("pattern1".to_string(), "kick".to_string(), 16);
+        timeline.add_segment(TimelineSegment::new(
+            "pattern1".to_string(),
+            vec![pattern],
+            0.0,
+            4,
+            TimeSignature::four_four(),
+            120.0,
+        ));
+
+        // A region dragged past the last segment loops at the end of the timeline
+        timeline.set_loop_region(6.0, 12.0);
+        assert_eq!(timeline.next_boundary(7.0), 8.0);
+        timeline.current_position = 7.5;
+        timeline.play();
+        assert!(timeline.advance_position(1.0));
+        assert!(timeline.is_playing());
+        assert!((timeline.current_position - 6.5).abs() < 1e-9);
+        assert!((timeline.audible_position(1.0) - 7.5).abs() < 1e-9);
+
+        // A region entirely past the end plays to the end and stops
+        timeline.set_loop_region(9.0, 12.0);
+        timeline.current_position = 7.5;
+        assert!(!timeline.advance_position(1.0));
+        assert!(!timeline.is_playing());
+
+        println!("✅ Loop region past the end test passed");
+    }
+
+    #[test]
+    fn test_loop_region_editing() {
+        let mut timeline = Timeline::new();
+        let pattern = Pattern::new("pattern1".to_string(), "kick".to_string(), 16);
+        let segment = TimelineSegment::new(
+            "pattern1".to_string(),
+            vec![pattern],
+            0.0,
+            4,
+            TimeSignature::four_four(),
+            120.0,
+        );
+        timeline.add_segment(segment);
+
+        // Points given in reverse order are normalized
+        timeline.set_loop_region(6.0, 2.0);
+        let region = timeline.loop_region.unwrap();
+        assert_eq!(region.start, 2.0);
+        assert_eq!(region.end, 6.0);
+
+        // In/out points move independently
+        timeline.set_loop_in(3.0);
+        timeline.set_loop_out(5.0);
+        let region = timeline.loop_region.unwrap();
+        assert_eq!((region.start, region.end), (3.0, 5.0));
+
+        // An in point past the out point extends the region to the timeline end
+        timeline.set_loop_in(6.0);
+        let region = timeline.loop_region.unwrap();
+        assert_eq!((region.start, region.end), (6.0, 8.0));
+
+        // Toggling keeps the region but disables looping
+        timeline.toggle_loop();
+        assert!(!timeline.is_looping());
+        assert!(timeline.loop_region.is_some());
+        timeline.play();
+        timeline.current_position = 7.5;
+        assert!(!timeline.advance_position(1.0));
+
+        // Toggling without a region loops the whole timeline
+        timeline.clear_loop_region();
+        timeline.toggle_loop();
+        let region = timeline.loop_region.unwrap();
+        assert_eq!((region.start, region.end), (0.0, 8.0));
+
+        // Degenerate regions are widened to the minimum length
+        timeline.set_loop_region(1.0, 1.0);
+        assert!(timeline.loop_region.unwrap().length() >= LoopRegion::MIN_LENGTH);
+
+        println!("✅ Loop region editing test passed");
+    }
+
+    #[test]
+    fn test_group_move_and_duplicate() {
+        let mut timeline = Timeline::new();
+        let make_segment = |start| {
+            let pattern = Pattern::new("pattern1".to_string(), "kick".to_string(), 16);
+            TimelineSegment::new(
+                "pattern1".to_string(),
+                vec![pattern],
+                start,
+                1, // 2 seconds at 120 BPM
+                TimeSignature::four_four(),
+                120.0,
+            )
+        };
+        let first = timeline.add_segment(make_segment(2.0));
+        let second = timeline.add_segment(make_segment(4.0));
+        let untouched = timeline.add_segment(make_segment(10.0));
+        let group = vec![first.clone(), second.clone()];
+
+        // Moving keeps the spacing inside the group and leaves other segments alone
+        assert_eq!(timeline.move_segments(&group, 3.0), 3.0);
+        assert_eq!(timeline.get_segment(&first).unwrap().start_time, 5.0);
+        assert_eq!(timeline.get_segment(&second).unwrap().start_time, 7.0);
+        assert_eq!(timeline.get_segment(&untouched).unwrap().start_time, 10.0);
+
+        // The earliest segment stops at zero, the others keep their offset
+        assert_eq!(timeline.move_segments(&group, -20.0), -5.0);
+        assert_eq!(timeline.get_segment(&first).unwrap().start_time, 0.0);
+        assert_eq!(timeline.get_segment(&second).unwrap().start_time, 2.0);
+
+        // Segments stay sorted after a group move
+        let starts: Vec<f64> = timeline.segments.iter().map(|s| s.start_time).collect();
+        assert!(starts.windows(2).all(|w| w[0] <= w[1]));
+
+        // Duplicating places the copy right after the group, preserving the arrangement
+        let copies = timeline.duplicate_segments(&group, 0.0);
+        assert_eq!(copies.len(), 2);
+        assert_eq!(timeline.get_segment(&copies[0]).unwrap().start_time, 4.0);
+        assert_eq!(timeline.get_segment(&copies[1]).unwrap().start_time, 6.0);
+        assert_eq!(timeline.segments.len(), 5);
+
+        // Unknown ids are ignored
+        assert_eq!(timeline.move_segments(&["missing".to_string()], 1.0), 0.0);
+        assert!(timeline
+            .duplicate_segments(&["missing".to_string()], 0.0)
+            .is_empty());
+
+        println!("✅ Group move and duplicate test passed");
+    }
+
+    #[test]
+    fn test_time_edits_split_on_bar_lines() {
+        let mut timeline = Timeline::new();
+        let segment = timeline.add_segment(one_bar_segment(0.0));
+        timeline
+            .get_segment_mut(&segment)
+            .unwrap()
+            .set_loop_count(3);
+
+        // Mid-bar positions move to the nearest bar line before splitting
+        assert_eq!(timeline.insert_bars(2.7, 1), 2.0);
+        let spans: Vec<(f64, f64, usize)> = timeline
+            .segments
+            .iter()
+            .map(|s| (s.start_time, s.end_time(), s.loop_count))
+            .collect();
+        assert_eq!(spans, vec![(0.0, 2.0, 1), (4.0, 8.0, 2)]);
+
+        timeline.delete_time_range(5.2, 7.0);
+        let spans: Vec<(f64, f64, usize)> = timeline
+            .segments
+            .iter()
+            .map(|s| (s.start_time, s.end_time(), s.loop_count))
+            .collect();
+        assert_eq!(spans, vec![(0.0, 2.0, 1), (4.0, 6.0, 1)]);
+
+        // Every piece is whole bars, so tempo changes keep the edit
+        for segment in &mut timeline.segments {
+            let duration = segment.duration;
+            segment.update_duration();
+            assert_eq!(segment.duration, duration);
+        }
+
+        // Ranges that snap to nothing leave the timeline alone
+        timeline.delete_time_range(4.2, 4.6);
+        assert_eq!(timeline.total_duration(), 6.0);
+
+        println!("✅ Bar line split test passed");
+    }
+
+    fn one_bar_segment(start: f64) -> TimelineSegment {
+        let pattern = Pattern::new("pattern1".to_string(), "kick".to_string(), 16);
+        TimelineSegment::new(
+            "pattern1".to_string(),
+            vec![pattern],
+            start,
+            1, // 2 seconds at 120 BPM
+            TimeSignature::four_four(),
+            120.0,
+        )
+    }
+
+    #[test]
+    fn test_ripple_edit_mode() {
+        let mut timeline = Timeline::new();
+        let first = timeline.add_segment(one_bar_segment(0.0));
+        let second = timeline.add_segment(one_bar_segment(2.0));
+        let third = timeline.add_segment(one_bar_segment(4.0));
+        timeline.add_marker("Outro", 4.0);
+
+        // Without ripple, edits leave overlaps and holes behind
+        timeline.edit_segment(&first, false, |s| s.set_loop_count(2));
+        assert_eq!(timeline.get_segment(&second).unwrap().start_time, 2.0);
+        timeline.edit_segment(&first, false, |s| s.set_loop_count(1));
+
+        // Growing a segment pushes everything after it, markers included
+        timeline.edit_segment(&first, true, |s| s.set_loop_count(2));
+        assert_eq!(timeline.get_segment(&second).unwrap().start_time, 4.0);
+        assert_eq!(timeline.get_segment(&third).unwrap().start_time, 6.0);
+        assert_eq!(timeline.markers[0].time, 6.0);
+
+        // Raising the BPM shortens the segment and pulls later ones back
+        timeline.edit_segment(&first, true, |s| s.set_bpm(240.0));
+        assert_eq!(timeline.get_segment(&first).unwrap().duration, 2.0);
+        assert_eq!(timeline.get_segment(&second).unwrap().start_time, 2.0);
+        assert_eq!(timeline.get_segment(&third).unwrap().start_time, 4.0);
+
+        // Deleting closes the gap
+        timeline.remove_segment(&second, true);
+        assert_eq!(timeline.get_segment(&third).unwrap().start_time, 2.0);
+        assert_eq!(timeline.markers[0].time, 2.0);
+        assert_eq!(timeline.total_duration(), 4.0);
+
+        // Global BPM changes keep the arrangement gap-free
+        timeline.set_global_bpm(60.0, true);
+        assert_eq!(timeline.get_segment(&first).unwrap().end_time(), 8.0);
+        assert_eq!(timeline.get_segment(&third).unwrap().start_time, 8.0);
+
+        // Unknown ids are ignored
+        assert!(!timeline.edit_segment("missing", true, |s| s.set_loop_count(8)));
+
+        println!("✅ Ripple edit mode test passed");
+    }
+
+    #[test]
+    fn test_insert_bars_and_delete_time_range() {
+        let mut timeline = Timeline::new();
+        let first = timeline.add_segment(one_bar_segment(0.0));
+        let second = timeline.add_segment(one_bar_segment(2.0));
+        timeline.add_marker("Chorus", 2.0);
+        timeline.set_loop_region(2.0, 4.0);
+
+        // Inserting at a segment boundary shifts everything after it
+        assert_eq!(timeline.insert_bars(2.0, 2), 4.0);
+        assert_eq!(timeline.get_segment(&first).unwrap().start_time, 0.0);
+        assert_eq!(timeline.get_segment(&second).unwrap().start_time, 6.0);
+        assert_eq!(timeline.markers[0].time, 6.0);
+        assert_eq!(timeline.loop_region.unwrap().start, 6.0);
+        assert_eq!(timeline.loop_region.unwrap().end, 8.0);
+
+        // Deleting the inserted time restores the original arrangement
+        timeline.delete_time_range(2.0, 6.0);
+        assert_eq!(timeline.segments.len(), 2);
+        assert_eq!(timeline.get_segment(&second).unwrap().start_time, 2.0);
+        assert_eq!(timeline.markers[0].time, 2.0);
+
+        // Inserting inside a segment splits it around the new gap
+        timeline.get_segment_mut(&second).unwrap().set_loop_count(2);
+        timeline.insert_bars(4.0, 1);
+        assert_eq!(timeline.segments.len(), 3);
+        let starts: Vec<f64> = timeline.segments.iter().map(|s| s.start_time).collect();
+        assert_eq!(starts, vec![0.0, 2.0, 6.0]);
+        assert_eq!(timeline.total_duration(), 8.0);
+
+        // Deleting a range that cuts through segments trims them to the nearest bar lines
+        // and removes markers inside
+        timeline.add_marker("Gone", 3.0);
+        timeline.seek(5.0);
+        timeline.delete_time_range(6.8, 1.2);
+        let spans: Vec<(f64, f64)> = timeline
+            .segments
+            .iter()
+            .map(|s| (s.start_time, s.end_time()))
+            .collect();
+        assert_eq!(spans, vec![(0.0, 2.0), (2.0, 4.0)]);
+        assert_eq!(timeline.markers.len(), 0);
+        assert_eq!(timeline.current_position, 2.0);
+        // The loop region was inside the deleted range
+        assert!(timeline.loop_region.is_none());
+
+        // Empty timelines insert bars at the default tempo
+        let mut empty = Timeline::new();
+        assert_eq!(empty.insert_bars(0.0, 1), 2.0);
+
+        println!("✅ Insert bars and delete time range test passed");
+    }
+
+    #[test]
+    fn test_timeline_markers() {
+        let mut timeline = Timeline::new();
+
+        // Markers are kept in chronological order regardless of insertion order
+        let chorus_id = timeline.add_marker("Chorus", 16.0);
+        let intro_id = timeline.add_marker("Intro", 0.0);
+        let verse_id = timeline.add_marker("Verse", 8.0);
+        let names: Vec<&str> = timeline.markers.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["Intro", "Verse", "Chorus"]);
+
+        // Section presets get their own colors
+        assert_eq!(
+            timeline.get_marker(&intro_id).unwrap().color,
+            MARKER_PRESETS[0].1
+        );
+        assert_eq!(
+            timeline.get_marker(&chorus_id).unwrap().color,
+            MARKER_PRESETS[2].1
+        );
+
+        // Next/previous skip a marker the playhead is sitting on
+        assert_eq!(timeline.next_marker(0.0).unwrap().id, verse_id);
+        assert_eq!(timeline.next_marker(8.0).unwrap().id, chorus_id);
+        assert!(timeline.next_marker(16.0).is_none());
+        assert_eq!(timeline.previous_marker(8.0).unwrap().id, intro_id);
+        assert_eq!(timeline.previous_marker(12.0).unwrap().id, verse_id);
+        assert!(timeline.previous_marker(0.0).is_none());
+
+        // Moving re-sorts, renaming keeps the position
+        assert!(timeline.move_marker(&verse_id, 20.0));
+        assert_eq!(timeline.markers.last().unwrap().id, verse_id);
+        assert!(timeline.rename_marker(&verse_id, "Drop"));
+        assert_eq!(timeline.get_marker(&verse_id).unwrap().name, "Drop");
+        assert_eq!(timeline.get_marker(&verse_id).unwrap().time, 20.0);
+
+        // Negative times are clamped to the timeline start
+        assert!(timeline.move_marker(&chorus_id, -5.0));
+        assert_eq!(timeline.get_marker(&chorus_id).unwrap().time, 0.0);
+
+        assert!(timeline.remove_marker(&intro_id).is_some());
+        assert!(timeline.remove_marker(&intro_id).is_none());
+        assert_eq!(timeline.markers.len(), 2);
+
+        println!("✅ Timeline markers test passed");
+    }
+
+    #[test]
+    fn test_new_markers_never_reuse_loaded_ids() {
+        // A project saved by another run holds ids this run's counter will hand out
+        let mut timeline = Timeline::new();
+        let next_id = generate_marker_id();
+        let upcoming: Vec<String> = (0..4)
+            .map(|offset| {
+                let number: usize = next_id.trim_start_matches("marker_").parse().unwrap();
+                format!("marker_{}", number + 1 + offset)
+            })
+            .collect();
+        for (index, id) in upcoming.iter().enumerate() {
+            let mut marker = Marker::new(format!("Loaded {}", index), index as f64, [0, 0, 0]);
+            marker.id = id.clone();
+            timeline.insert_marker(marker);
+        }
+
+        let added_id = timeline.add_marker("Drop", 10.0);
+        assert!(!upcoming.contains(&added_id));
+        assert_eq!(timeline.markers.len(), 5);
+
+        // Edits by id touch only the marker they name
+        assert!(timeline.rename_marker(&added_id, "Break"));
+        assert_eq!(
+            timeline
+                .markers
+                .iter()
+                .filter(|m| m.name == "Break")
+                .count(),
+            1
+        );
+        assert!(timeline.remove_marker(&added_id).is_some());
+        assert_eq!(timeline.markers.len(), 4);
+
+        println!("✅ Unique marker id test passed");
+    }
+
     #[test]
     fn test_timeline_with_different_time_signatures() {
         let mut timeline = Timeline::new();
@@ -629,7 +1608,7 @@ mod tests {
         let initial_total_duration = timeline.total_duration();
 
         // Test global BPM update - set all segments to 100 BPM
-        timeline.set_global_bpm(100.0);
+        timeline.set_global_bpm(100.0, false);
 
         // Check that all segments now have 100 BPM
         assert_eq!(timeline.get_segment(&id1).unwrap().bpm, 100.0);
@@ -718,6 +1697,119 @@ mod tests {
         );
     }
 
+    /// Play a segment through the audio engine's step timing with a one-sample click
+    /// on every step, returning the sample index of each trigger
+    fn render_step_triggers(
+        segment: &TimelineSegment,
+        sample_rate: f32,
+        buffer_size: usize,
+    ) -> (Vec<usize>, crate::audio::engine::AudioState) {
+        let mut bank = crate::audio::SampleBank::new();
+        bank.add_sample(
+            "click".to_string(),
+            crate::audio::samples::Sample::from_data(vec![1.0], sample_rate as u32, 1),
+        );
+        let mut pattern = Pattern::new("click".to_string(), "click".to_string(), 16);
+        for step in &mut pattern.steps {
+            step.active = true;
+        }
+        let patterns = vec![pattern];
+
+        let mut audio_state = crate::audio::engine::AudioState::new(sample_rate, segment.bpm);
+        let total_samples = (segment.duration * sample_rate as f64).ceil() as usize;
+        let mut buffer = vec![0.0f32; buffer_size];
+        let mut triggers = Vec::new();
+        let mut rendered = 0;
+        while rendered < total_samples {
+            let len = buffer_size.min(total_samples - rendered);
+            buffer[..len].fill(0.0);
+            audio_state.process_patterns(
+                &mut buffer[..len],
+                &bank,
+                &patterns,
+                segment.bpm,
+                sample_rate,
+            );
+            for (i, sample) in buffer[..len].iter().enumerate() {
+                if *sample != 0.0 {
+                    triggers.push(rendered + i);
+                }
+            }
+            rendered += len;
+        }
+
+        (triggers, audio_state)
+    }
+
+    /// Each trigger must land on the first sample at or after its step's exact start
+    fn assert_triggers_locked(triggers: &[usize], segment: &TimelineSegment, sample_rate: f32) {
+        let samples_per_step = sample_rate as f64 / (segment.bpm as f64 / 60.0 * 4.0);
+        let expected_steps = segment.loop_count * segment.time_signature.numerator as usize * 4;
+        assert_eq!(
+            triggers.len(),
+            expected_steps,
+            "Every step should trigger once"
+        );
+
+        for (step, &trigger) in triggers.iter().enumerate() {
+            let ideal = step as f64 * samples_per_step;
+            assert!(
+                trigger as f64 >= ideal - 1e-6 && (trigger as f64) < ideal + 1.0 + 1e-6,
+                "Step {} at {} BPM triggered at sample {}, expected {:.3}",
+                step,
+                segment.bpm,
+                trigger,
+                ideal
+            );
+        }
+    }
+
+    #[test]
+    fn test_step_triggers_fill_93_bpm_4_bars() {
+        // At 44.1 kHz a step at 93 BPM is 7112.9 samples; truncating it to 7112 put the
+        // last step of 4 bars 57 samples early and ended the audio loop before the segment
+        let segment = TimelineSegment::new(
+            "test_pattern".to_string(),
+            vec![],
+            0.0,
+            4,
+            TimeSignature::four_four(),
+            93.0,
+        );
+
+        let (triggers, audio_state) = render_step_triggers(&segment, 44100.0, 512);
+        assert_triggers_locked(&triggers, &segment, 44100.0);
+
+        // Exactly 4 bars of steps have played where the segment ends
+        assert_eq!(audio_state.current_step, 64);
+
+        println!("✅ Step timing at 93 BPM over 4 bars test passed");
+    }
+
+    #[test]
+    fn test_step_timing_does_not_drift_over_long_segments() {
+        for (bpm, sample_rate, buffer_size) in [
+            (93.0, 44100.0, 64),
+            (127.0, 48000.0, 441),
+            (87.5, 96000.0, 1024),
+            (173.0, 22050.0, 4096),
+        ] {
+            let segment = TimelineSegment::new(
+                "test_pattern".to_string(),
+                vec![],
+                0.0,
+                64,
+                TimeSignature::four_four(),
+                bpm,
+            );
+
+            let (triggers, _) = render_step_triggers(&segment, sample_rate, buffer_size);
+            assert_triggers_locked(&triggers, &segment, sample_rate);
+        }
+
+        println!("✅ Step timing drift test passed");
+    }
+
     #[test]
     fn test_audio_timing_vs_timeline_timing() {
         // Test to understand the discrepancy between audio playback speed and timeline duration
@@ -848,8 +1940,8 @@ mod tests {
             (5.0, 0),    // Segment start -> step 0
             (6.29, 7),   // 1.29 seconds into segment -> (1.29 * 6.2) as usize = 7
             (7.58, 15),  // 2.58 seconds into segment -> (2.58 * 6.2) as usize = 15
-            (8.87, 7),   // 3.87 seconds into segment -> (3.87 * 6.2) as usize = 23 % 16 = 7
-            (10.16, 15), // 5.16 seconds into segment -> (5.16 * 6.2) as usize = 31 % 16 = 15
+            (8.87, 23),  // 3.87 seconds into segment -> (3.87 * 6.2) as usize = 23
+            (10.16, 31), // 5.16 seconds into segment -> (5.16 * 6.2) as usize = 31
         ];
 
         for (timeline_pos, expected_step) in test_positions {
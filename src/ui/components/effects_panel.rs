@@ -0,0 +1,172 @@
+use crate::audio::effects::{
+    CompressorSettings, EqSettings, LimiterSettings, MasterEffects, ReverbSettings,
+};
+use eframe::egui;
+use std::ops::RangeInclusive;
+
+/// Window for editing the project's master effects chain
+#[derive(Default)]
+pub struct EffectsPanel {
+    pub open: bool,
+}
+
+impl EffectsPanel {
+    /// Show the window while it is open. Returns true when a parameter changed.
+    pub fn show(&mut self, ctx: &egui::Context, effects: &mut MasterEffects) -> bool {
+        let mut changed = false;
+        let mut open = self.open;
+
+        egui::Window::new("Master Effects")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.weak("Applied in order to everything the project plays");
+                ui.add_space(6.0);
+
+                changed |= Self::eq_section(ui, &mut effects.eq);
+                ui.separator();
+                changed |= Self::compressor_section(ui, &mut effects.compressor);
+                ui.separator();
+                changed |= Self::reverb_section(ui, &mut effects.reverb);
+                ui.separator();
+                changed |= Self::limiter_section(ui, &mut effects.limiter);
+
+                ui.add_space(6.0);
+                if ui.button("Reset All").clicked() {
+                    *effects = MasterEffects::default();
+                    changed = true;
+                }
+            });
+
+        self.open = open;
+        changed
+    }
+
+    fn eq_section(ui: &mut egui::Ui, eq: &mut EqSettings) -> bool {
+        let mut changed = ui.checkbox(&mut eq.enabled, "EQ").changed();
+        ui.add_enabled_ui(eq.enabled, |ui| {
+            egui::Grid::new("master_eq_grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    changed |= frequency_row(ui, "Low", &mut eq.low_freq, 20.0..=500.0);
+                    changed |= gain_row(ui, "Low Gain", &mut eq.low_gain_db);
+                    changed |= frequency_row(ui, "Mid", &mut eq.mid_freq, 200.0..=8000.0);
+                    changed |= slider_row(ui, "Mid Q", &mut eq.mid_q, 0.1..=10.0, "");
+                    changed |= gain_row(ui, "Mid Gain", &mut eq.mid_gain_db);
+                    changed |= frequency_row(ui, "High", &mut eq.high_freq, 2000.0..=16000.0);
+                    changed |= gain_row(ui, "High Gain", &mut eq.high_gain_db);
+                });
+        });
+        changed
+    }
+
+    fn compressor_section(ui: &mut egui::Ui, compressor: &mut CompressorSettings) -> bool {
+        let mut changed = ui.checkbox(&mut compressor.enabled, "Compressor").changed();
+        ui.add_enabled_ui(compressor.enabled, |ui| {
+            egui::Grid::new("master_compressor_grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    changed |= slider_row(
+                        ui,
+                        "Threshold",
+                        &mut compressor.threshold_db,
+                        -60.0..=0.0,
+                        " dB",
+                    );
+                    changed |= slider_row(ui, "Ratio", &mut compressor.ratio, 1.0..=20.0, ":1");
+                    changed |=
+                        slider_row(ui, "Attack", &mut compressor.attack_ms, 0.1..=100.0, " ms");
+                    changed |= slider_row(
+                        ui,
+                        "Release",
+                        &mut compressor.release_ms,
+                        10.0..=1000.0,
+                        " ms",
+                    );
+                    changed |=
+                        slider_row(ui, "Makeup", &mut compressor.makeup_db, 0.0..=24.0, " dB");
+                });
+        });
+        changed
+    }
+
+    fn reverb_section(ui: &mut egui::Ui, reverb: &mut ReverbSettings) -> bool {
+        let mut changed = ui.checkbox(&mut reverb.enabled, "Reverb").changed();
+        ui.add_enabled_ui(reverb.enabled, |ui| {
+            egui::Grid::new("master_reverb_grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    changed |= slider_row(ui, "Room Size", &mut reverb.room_size, 5.0..=50.0, " m");
+                    changed |= slider_row(ui, "Decay", &mut reverb.time, 0.1..=10.0, " s");
+                    changed |= slider_row(ui, "Damping", &mut reverb.damping, 0.0..=1.0, "");
+                    changed |= percent_row(ui, "Mix", &mut reverb.mix);
+                });
+        });
+        changed
+    }
+
+    fn limiter_section(ui: &mut egui::Ui, limiter: &mut LimiterSettings) -> bool {
+        let mut changed = ui.checkbox(&mut limiter.enabled, "Limiter").changed();
+        ui.add_enabled_ui(limiter.enabled, |ui| {
+            egui::Grid::new("master_limiter_grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    changed |=
+                        slider_row(ui, "Ceiling", &mut limiter.ceiling_db, -12.0..=0.0, " dB");
+                });
+        });
+        changed
+    }
+}
+
+pub(super) fn slider_row(
+    ui: &mut egui::Ui,
+    label: &str,
+    value: &mut f32,
+    range: RangeInclusive<f32>,
+    suffix: &str,
+) -> bool {
+    ui.label(label);
+    let changed = ui
+        .add(egui::Slider::new(value, range).suffix(suffix))
+        .changed();
+    ui.end_row();
+    changed
+}
+
+pub(super) fn frequency_row(
+    ui: &mut egui::Ui,
+    label: &str,
+    value: &mut f32,
+    range: RangeInclusive<f32>,
+) -> bool {
+    ui.label(label);
+    let changed = ui
+        .add(
+            egui::Slider::new(value, range)
+                .logarithmic(true)
+                .suffix(" Hz")
+                .max_decimals(0),
+        )
+        .changed();
+    ui.end_row();
+    changed
+}
+
+fn gain_row(ui: &mut egui::Ui, label: &str, value: &mut f32) -> bool {
+    slider_row(ui, label, value, -18.0..=18.0, " dB")
+}
+
+/// Slider for a 0 to 1 amount shown as a percentage
+pub(super) fn percent_row(ui: &mut egui::Ui, label: &str, value: &mut f32) -> bool {
+    ui.label(label);
+    let changed = ui
+        .add(
+            egui::Slider::new(value, 0.0..=1.0)
+                .custom_formatter(|n, _| format!("{:.0}%", n * 100.0)),
+        )
+        .changed();
+    ui.end_row();
+    changed
+}
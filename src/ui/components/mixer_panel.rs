@@ -0,0 +1,256 @@
+use super::effects_panel::{frequency_row, percent_row, slider_row};
+use crate::audio::mixer::{
+    BitcrusherSettings, DelayBus, FilterMode, FilterSettings, MixerSettings, ReverbBus,
+    SaturationSettings, TrackEffects, TransientSettings,
+};
+use eframe::egui;
+
+/// Window for editing each track's insert effects and sends, and the buses they share
+#[derive(Default)]
+pub struct MixerPanel {
+    pub open: bool,
+    selected_track: Option<String>, // Sample name of the track being edited
+}
+
+impl MixerPanel {
+    /// Show the window while it is open. `tracks` pairs each sample the project plays
+    /// with the name to show for its track. Returns true when a parameter changed.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        mixer: &mut MixerSettings,
+        tracks: &[(String, String)],
+    ) -> bool {
+        let mut changed = false;
+        let mut open = self.open;
+
+        let tracks = with_leftover_tracks(tracks, mixer.tracks.keys());
+        keep_selection(&mut self.selected_track, &tracks);
+
+        egui::Window::new("Track Effects")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                match self.selected_track.clone() {
+                    Some(selected) => {
+                        track_picker(ui, "mixer_track", &tracks, &mut self.selected_track);
+                        ui.weak("Inserts run top to bottom, then feed the sends");
+                        ui.add_space(6.0);
+
+                        changed |= Self::track_section(ui, mixer, &selected);
+                    }
+                    None => {
+                        ui.weak("Add patterns to the timeline to give their tracks effects");
+                    }
+                }
+
+                ui.separator();
+                ui.strong("Send Buses");
+                changed |= Self::reverb_bus_section(ui, &mut mixer.reverb_bus);
+                changed |= Self::delay_bus_section(ui, &mut mixer.delay_bus);
+            });
+
+        self.open = open;
+        changed
+    }
+
+    /// Inserts and sends of the track playing `sample_name`
+    fn track_section(ui: &mut egui::Ui, mixer: &mut MixerSettings, sample_name: &str) -> bool {
+        let mut effects = mixer.track(sample_name);
+
+        let mut changed = Self::filter_section(ui, &mut effects.filter);
+        ui.separator();
+        changed |= Self::saturation_section(ui, &mut effects.saturation);
+        ui.separator();
+        changed |= Self::transient_section(ui, &mut effects.transient);
+        ui.separator();
+        changed |= Self::bitcrusher_section(ui, &mut effects.bitcrusher);
+        ui.separator();
+
+        ui.label("Sends");
+        egui::Grid::new("track_sends_grid")
+            .num_columns(2)
+            .show(ui, |ui| {
+                changed |= percent_row(ui, "Reverb", &mut effects.reverb_send);
+                changed |= percent_row(ui, "Delay", &mut effects.delay_send);
+            });
+
+        ui.add_space(6.0);
+        if ui.button("Reset Track").clicked() {
+            effects = TrackEffects::default();
+            changed = true;
+        }
+
+        if changed {
+            // Tracks left at the defaults play dry and aren't saved
+            if effects == TrackEffects::default() {
+                mixer.tracks.remove(sample_name);
+            } else {
+                mixer.tracks.insert(sample_name.to_string(), effects);
+            }
+        }
+        changed
+    }
+
+    fn filter_section(ui: &mut egui::Ui, filter: &mut FilterSettings) -> bool {
+        let mut changed = ui.checkbox(&mut filter.enabled, "Filter").changed();
+        ui.add_enabled_ui(filter.enabled, |ui| {
+            egui::Grid::new("track_filter_grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Mode");
+                    egui::ComboBox::from_id_source("track_filter_mode")
+                        .selected_text(filter.mode.display_name())
+                        .show_ui(ui, |ui| {
+                            for mode in [
+                                FilterMode::Lowpass,
+                                FilterMode::Highpass,
+                                FilterMode::Bandpass,
+                            ] {
+                                changed |= ui
+                                    .selectable_value(&mut filter.mode, mode, mode.display_name())
+                                    .changed();
+                            }
+                        });
+                    ui.end_row();
+                    changed |= frequency_row(ui, "Cutoff", &mut filter.cutoff, 20.0..=20000.0);
+                    changed |= slider_row(ui, "Q", &mut filter.q, 0.1..=10.0, "");
+                });
+        });
+        changed
+    }
+
+    fn saturation_section(ui: &mut egui::Ui, saturation: &mut SaturationSettings) -> bool {
+        let mut changed = ui.checkbox(&mut saturation.enabled, "Saturation").changed();
+        ui.add_enabled_ui(saturation.enabled, |ui| {
+            egui::Grid::new("track_saturation_grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    changed |= slider_row(ui, "Drive", &mut saturation.drive_db, 0.0..=24.0, " dB");
+                    changed |= percent_row(ui, "Mix", &mut saturation.mix);
+                });
+        });
+        changed
+    }
+
+    fn transient_section(ui: &mut egui::Ui, transient: &mut TransientSettings) -> bool {
+        let mut changed = ui
+            .checkbox(&mut transient.enabled, "Transient Shaper")
+            .changed();
+        ui.add_enabled_ui(transient.enabled, |ui| {
+            egui::Grid::new("track_transient_grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    changed |= slider_row(ui, "Attack", &mut transient.attack, -1.0..=1.0, "");
+                    changed |= slider_row(ui, "Sustain", &mut transient.sustain, -1.0..=1.0, "");
+                });
+        });
+        changed
+    }
+
+    fn bitcrusher_section(ui: &mut egui::Ui, crusher: &mut BitcrusherSettings) -> bool {
+        let mut changed = ui.checkbox(&mut crusher.enabled, "Bitcrusher").changed();
+        ui.add_enabled_ui(crusher.enabled, |ui| {
+            egui::Grid::new("track_bitcrusher_grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Bits");
+                    changed |= ui
+                        .add(egui::Slider::new(&mut crusher.bits, 1..=16))
+                        .changed();
+                    ui.end_row();
+                    ui.label("Downsample");
+                    changed |= ui
+                        .add(egui::Slider::new(&mut crusher.downsample, 1..=16).suffix("x"))
+                        .changed();
+                    ui.end_row();
+                });
+        });
+        changed
+    }
+
+    fn reverb_bus_section(ui: &mut egui::Ui, bus: &mut ReverbBus) -> bool {
+        let mut changed = false;
+        ui.label("Reverb");
+        egui::Grid::new("reverb_bus_grid")
+            .num_columns(2)
+            .show(ui, |ui| {
+                changed |= slider_row(ui, "Room Size", &mut bus.room_size, 5.0..=50.0, " m");
+                changed |= slider_row(ui, "Decay", &mut bus.time, 0.1..=10.0, " s");
+                changed |= slider_row(ui, "Damping", &mut bus.damping, 0.0..=1.0, "");
+                changed |= percent_row(ui, "Return", &mut bus.level);
+            });
+        changed
+    }
+
+    fn delay_bus_section(ui: &mut egui::Ui, bus: &mut DelayBus) -> bool {
+        let mut changed = false;
+        ui.label("Delay");
+        egui::Grid::new("delay_bus_grid")
+            .num_columns(2)
+            .show(ui, |ui| {
+                changed |= slider_row(ui, "Time", &mut bus.time_ms, 1.0..=2000.0, " ms");
+                changed |= slider_row(ui, "Feedback", &mut bus.feedback, 0.0..=0.95, "");
+                changed |= percent_row(ui, "Return", &mut bus.level);
+            });
+        changed
+    }
+}
+
+/// `tracks` followed by the tracks in `sample_names` whose patterns are gone, which
+/// keep their settings so they can still be cleared
+pub(super) fn with_leftover_tracks<'a>(
+    tracks: &[(String, String)],
+    sample_names: impl Iterator<Item = &'a String>,
+) -> Vec<(String, String)> {
+    let mut tracks = tracks.to_vec();
+    for sample_name in sample_names {
+        if !tracks.iter().any(|(sample, _)| sample == sample_name) {
+            tracks.push((sample_name.clone(), sample_name.clone()));
+        }
+    }
+    tracks
+}
+
+/// Select the first track unless the selected one is still there
+pub(super) fn keep_selection(selected: &mut Option<String>, tracks: &[(String, String)]) {
+    let valid = selected
+        .as_ref()
+        .is_some_and(|selected| tracks.iter().any(|(sample, _)| sample == selected));
+    if !valid {
+        *selected = tracks.first().map(|(sample, _)| sample.clone());
+    }
+}
+
+/// Combo box choosing a track by sample name
+pub(super) fn track_picker(
+    ui: &mut egui::Ui,
+    id: &str,
+    tracks: &[(String, String)],
+    selected: &mut Option<String>,
+) {
+    let current = selected.clone().unwrap_or_default();
+    ui.horizontal(|ui| {
+        ui.label("Track");
+        egui::ComboBox::from_id_source(id)
+            .selected_text(track_label(tracks, &current))
+            .show_ui(ui, |ui| {
+                for (sample, _) in tracks {
+                    ui.selectable_value(
+                        selected,
+                        Some(sample.clone()),
+                        track_label(tracks, sample),
+                    );
+                }
+            });
+    });
+}
+
+/// Name shown for the track playing `sample_name`, with the sample when they differ
+fn track_label(tracks: &[(String, String)], sample_name: &str) -> String {
+    match tracks.iter().find(|(sample, _)| sample == sample_name) {
+        Some((_, name)) if name != sample_name => format!("{} ({})", name, sample_name),
+        _ => sample_name.to_string(),
+    }
+}
@@ -1,13 +1,21 @@
+pub mod effects_panel;
 pub mod loop_length_control;
+pub mod mixer_panel;
 pub mod pattern_grid;
+pub mod project_defaults_panel;
 pub mod settings_dialog;
+pub mod synth_panel;
 pub mod tempo;
 pub mod time_signature_control;
 pub mod timeline_view;
 pub mod transport;
 
+pub use effects_panel::EffectsPanel;
+pub use mixer_panel::MixerPanel;
 pub use pattern_grid::PatternGrid;
+pub use project_defaults_panel::ProjectDefaultsPanel;
 pub use settings_dialog::SettingsDialog;
+pub use synth_panel::{SynthChange, SynthPanel};
 pub use tempo::TempoControl;
 pub use time_signature_control::TimeSignatureControl;
 pub use timeline_view::TimelineView;
@@ -78,25 +78,28 @@ fn get_downbeat_header_color(visuals: &egui::Visuals) -> egui::Color32 {
 pub struct PatternGrid;
 
 impl PatternGrid {
+    /// Show the selected segment's patterns. The step being heard is highlighted, with
+    /// the playhead moved back by `output_latency` seconds to match the audio.
     pub fn show(
         ui: &mut egui::Ui,
         timeline: &Arc<Mutex<Timeline>>,
         selected_segment_id: Option<&str>,
+        output_latency: f64,
     ) {
         // Timeline mode - get time signature from selected segment
-        let (current_step, loop_length, time_signature) = if let Some(id) = selected_segment_id {
+        let (loop_length, time_signature) = if let Some(id) = selected_segment_id {
             if let Ok(timeline) = timeline.lock() {
                 if let Some(segment) = timeline.get_segment(id) {
                     let loop_len = segment.patterns.get(0).map(|p| p.steps.len()).unwrap_or(16);
-                    (0, loop_len, segment.time_signature)
+                    (loop_len, segment.time_signature)
                 } else {
-                    (0, 16, crate::audio::TimeSignature::four_four())
+                    (16, crate::audio::TimeSignature::four_four())
                 }
             } else {
-                (0, 16, crate::audio::TimeSignature::four_four())
+                (16, crate::audio::TimeSignature::four_four())
             }
         } else {
-            (0, 16, crate::audio::TimeSignature::four_four())
+            (16, crate::audio::TimeSignature::four_four())
         };
 
         // Determine which segment to display patterns from
@@ -120,11 +123,20 @@ impl PatternGrid {
             return;
         }
 
-        // Get patterns from the selected timeline segment
-        let (patterns, _segment_name) = {
+        // Get patterns from the selected timeline segment, and how far into it playback is
+        let (patterns, _segment_name, steps_elapsed) = {
             if let Ok(timeline) = timeline.lock() {
                 if let Some(segment) = timeline.get_segment(&segment_to_display) {
-                    (segment.patterns.clone(), segment.pattern_id.clone())
+                    let steps_elapsed = if timeline.is_playing() {
+                        segment.steps_elapsed_at(timeline.audible_position(output_latency))
+                    } else {
+                        None
+                    };
+                    (
+                        segment.patterns.clone(),
+                        segment.pattern_id.clone(),
+                        steps_elapsed,
+                    )
                 } else {
                     ui.label("Selected segment not found");
                     return;
@@ -166,11 +178,12 @@ impl PatternGrid {
                     ui.add_space(SPACING);
 
                     // Step number headers with time signature-aware beat grouping
+                    let current_step = steps_elapsed.map(|steps| steps % loop_length.max(1));
                     for step in 0..loop_length {
                         let is_beat_boundary = time_signature.is_beat_boundary(step, loop_length);
                         let is_downbeat = time_signature.is_downbeat(step, loop_length);
 
-                        let text_color = if step == current_step {
+                        let text_color = if Some(step) == current_step {
                             egui::Color32::YELLOW // Current step (highest priority)
                         } else if is_downbeat {
                             get_downbeat_header_color(&ui.visuals())
@@ -227,6 +240,9 @@ impl PatternGrid {
                     let pattern = &patterns[pattern_index];
                     let pattern_name = pattern.name.clone();
                     let pattern_steps = pattern.steps.clone();
+                    // Each pattern loops over its own steps
+                    let current_step =
+                        steps_elapsed.map(|steps| steps % pattern_steps.len().max(1));
 
                     ui.horizontal(|ui| {
                         // Track name column with fixed width and right alignment
@@ -278,7 +294,7 @@ impl PatternGrid {
                             let button_color = get_step_button_colors(
                                 &ui.visuals(),
                                 step.active,
-                                step_index == current_step,
+                                Some(step_index) == current_step,
                                 is_downbeat,
                                 is_beat_boundary,
                             );
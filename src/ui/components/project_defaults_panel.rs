@@ -0,0 +1,85 @@
+use crate::audio::TimeSignature;
+use crate::project::ProjectDefaults;
+use eframe::egui;
+
+const DENOMINATORS: [u8; 5] = [1, 2, 4, 8, 16];
+const PATTERN_LENGTH_PRESETS: [usize; 4] = [8, 16, 24, 32];
+
+/// Window for the time signature and pattern length new segments start with
+#[derive(Default)]
+pub struct ProjectDefaultsPanel {
+    pub open: bool,
+}
+
+impl ProjectDefaultsPanel {
+    /// Show the window while it is open. Returns true when a default changed.
+    pub fn show(&mut self, ctx: &egui::Context, defaults: &mut ProjectDefaults) -> bool {
+        let mut changed = false;
+        let mut open = self.open;
+
+        egui::Window::new("Project Defaults")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.weak("Used by segments added to this project from now on");
+                ui.add_space(6.0);
+
+                egui::Grid::new("project_defaults_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Time Signature");
+                        changed |= Self::time_signature_row(ui, &mut defaults.time_signature);
+                        ui.end_row();
+
+                        // The range matches what project validation accepts
+                        ui.label("Pattern Length");
+                        changed |= ui
+                            .add(
+                                egui::Slider::new(&mut defaults.pattern_length, 4..=64)
+                                    .suffix(" steps"),
+                            )
+                            .changed();
+                        ui.end_row();
+
+                        ui.label("");
+                        ui.horizontal(|ui| {
+                            for length in PATTERN_LENGTH_PRESETS {
+                                if ui.small_button(format!("{} steps", length)).clicked() {
+                                    defaults.pattern_length = length;
+                                    changed = true;
+                                }
+                            }
+                        });
+                        ui.end_row();
+                    });
+            });
+
+        self.open = open;
+        changed
+    }
+
+    fn time_signature_row(ui: &mut egui::Ui, time_signature: &mut TimeSignature) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            changed |= ui
+                .add(egui::Slider::new(&mut time_signature.numerator, 1..=16))
+                .changed();
+            ui.label("/");
+            egui::ComboBox::from_id_source("project_defaults_denominator")
+                .selected_text(time_signature.denominator.to_string())
+                .show_ui(ui, |ui| {
+                    for denominator in DENOMINATORS {
+                        changed |= ui
+                            .selectable_value(
+                                &mut time_signature.denominator,
+                                denominator,
+                                denominator.to_string(),
+                            )
+                            .changed();
+                    }
+                });
+        });
+        changed
+    }
+}
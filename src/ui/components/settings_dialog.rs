@@ -1,3 +1,4 @@
+use crate::audio::backend::AudioBackend;
 use crate::audio::engine::{AudioDeviceInfo, AudioEngine};
 use crate::settings::{AppSettings, AudioSettings, DefaultSettings, UISettings};
 use eframe::egui;
@@ -16,6 +17,7 @@ pub struct SettingsDialog {
     device_refresh_requested: bool,
     device_test_status: Option<DeviceTestResult>,
     last_test_device: Option<String>,
+    output_latency: Option<f64>, // Seconds, as measured by the running engine
 
     // Pending changes (for delayed application)
     pending_ui_scale: Option<f32>,
@@ -63,6 +65,7 @@ impl SettingsDialog {
             device_refresh_requested: false,
             device_test_status: None,
             last_test_device: None,
+            output_latency: None,
             pending_ui_scale: None,
         }
     }
@@ -316,6 +319,17 @@ impl SettingsDialog {
 
         ui.add_space(10.0);
 
+        // Output latency of the running engine, which the playhead display compensates for
+        ui.horizontal(|ui| {
+            ui.label("Output Latency:");
+            match self.output_latency {
+                Some(latency) => ui.label(format!("{:.1} ms", latency * 1000.0)),
+                None => ui.weak("Audio not running"),
+            };
+        });
+
+        ui.add_space(10.0);
+
         // Master Volume
         ui.horizontal(|ui| {
             ui.label("Master Volume:");
@@ -332,6 +346,60 @@ impl SettingsDialog {
 
         ui.add_space(10.0);
 
+        // Output backend: a device, nothing (headless) or a WAV file
+        ui.horizontal(|ui| {
+            ui.label("Output:");
+
+            let backend = &mut self.settings.audio.backend;
+            let selected_text = match backend {
+                AudioBackend::Device => "Audio device",
+                AudioBackend::Null => "None (silent)",
+                AudioBackend::File(_) => "WAV file",
+            };
+            egui::ComboBox::from_id_source("audio_backend_combo")
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    if ui
+                        .selectable_value(backend, AudioBackend::Device, "Audio device")
+                        .clicked()
+                    {
+                        changed = true;
+                    }
+                    if ui
+                        .selectable_value(backend, AudioBackend::Null, "None (silent)")
+                        .clicked()
+                    {
+                        changed = true;
+                    }
+                    let is_file = matches!(backend, AudioBackend::File(_));
+                    if ui.selectable_label(is_file, "WAV file").clicked() && !is_file {
+                        *backend = AudioBackend::File(
+                            dirs::home_dir()
+                                .unwrap_or_default()
+                                .join("beatr-output.wav"),
+                        );
+                        changed = true;
+                    }
+                });
+
+            if let AudioBackend::File(path) = backend {
+                let mut path_text = path.display().to_string();
+                if ui.text_edit_singleline(&mut path_text).changed() {
+                    *path = std::path::PathBuf::from(path_text);
+                    changed = true;
+                }
+            }
+        });
+
+        if self.settings.audio.backend != AudioBackend::Device {
+            ui.horizontal(|ui| {
+                ui.add_space(120.0); // Align with label
+                ui.label("Playback runs on a timer; the device below is not used");
+            });
+        }
+
+        ui.add_space(10.0);
+
         // Audio Device Selection with Status Indicators
         ui.horizontal(|ui| {
             ui.label("Audio Device:");
@@ -626,6 +694,49 @@ impl SettingsDialog {
             }
         });
 
+        ui.add_space(10.0);
+
+        // Autosave interval for crash recovery
+        ui.horizontal(|ui| {
+            ui.label("Autosave:");
+            let mut autosave_enabled = self.settings.defaults.autosave_interval_secs > 0;
+            if ui.checkbox(&mut autosave_enabled, "Enabled").changed() {
+                self.settings.defaults.autosave_interval_secs = if autosave_enabled {
+                    DefaultSettings::default().autosave_interval_secs
+                } else {
+                    0
+                };
+                changed = true;
+            }
+
+            if autosave_enabled {
+                ui.label("every");
+                if ui
+                    .add(
+                        egui::Slider::new(&mut self.settings.defaults.autosave_interval_secs, 10..=3600)
+                            .logarithmic(true)
+                            .custom_formatter(|n, _| format!("{:.0} s", n)),
+                    )
+                    .changed()
+                {
+                    changed = true;
+                }
+            }
+        });
+
+        ui.add_space(10.0);
+
+        // Startup behaviour
+        if ui
+            .checkbox(
+                &mut self.settings.recent.reopen_last_project,
+                "Reopen last project on startup",
+            )
+            .changed()
+        {
+            changed = true;
+        }
+
         changed
     }
 
@@ -748,6 +859,11 @@ impl SettingsDialog {
         }
     }
 
+    /// Latency reported by the audio engine, or None when audio is not running
+    pub fn set_output_latency(&mut self, output_latency: Option<f64>) {
+        self.output_latency = output_latency;
+    }
+
     /// Clear device test status
     pub fn clear_device_test_status(&mut self) {
         self.device_test_status = None;
@@ -817,6 +933,18 @@ impl SettingsDialog {
                     ui.monospace(&keyboard.timeline_jump_forward);
                     ui.end_row();
 
+                    ui.label("Previous Marker:");
+                    ui.monospace(&keyboard.timeline_prev_marker);
+                    ui.end_row();
+
+                    ui.label("Next Marker:");
+                    ui.monospace(&keyboard.timeline_next_marker);
+                    ui.end_row();
+
+                    ui.label("Add Marker:");
+                    ui.monospace(&keyboard.marker_add);
+                    ui.end_row();
+
                     ui.label("Go to Start:");
                     ui.monospace(&keyboard.timeline_start);
                     ui.end_row();
@@ -824,6 +952,18 @@ impl SettingsDialog {
                     ui.label("Go to End:");
                     ui.monospace(&keyboard.timeline_end);
                     ui.end_row();
+
+                    ui.label("Set Loop In:");
+                    ui.monospace(&keyboard.loop_set_in);
+                    ui.end_row();
+
+                    ui.label("Set Loop Out:");
+                    ui.monospace(&keyboard.loop_set_out);
+                    ui.end_row();
+
+                    ui.label("Toggle Loop:");
+                    ui.monospace(&keyboard.loop_toggle);
+                    ui.end_row();
                 });
         });
 
@@ -0,0 +1,198 @@
+use super::effects_panel::{frequency_row, percent_row, slider_row};
+use super::mixer_panel::{keep_selection, track_picker, with_leftover_tracks};
+use crate::audio::synth::{DrumModel, DrumVoice, SynthParams};
+use eframe::egui;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// What the drum synth window changed
+pub enum SynthChange {
+    Voice(String),      // The voice of this sample
+    Seed,               // The kit seed, which every voice's noise comes from
+    LoadFile(String),   // This sample should play a WAV file the user picks
+    UnloadFile(String), // This sample should go back to its synthesized voice
+}
+
+/// Window for shaping each track's synthesized drum sound
+#[derive(Default)]
+pub struct SynthPanel {
+    pub open: bool,
+    selected_track: Option<String>, // Sample name of the track being edited
+}
+
+impl SynthPanel {
+    /// Show the window while it is open. `tracks` pairs each sample the project plays
+    /// with the name to show for its track.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        voices: &mut BTreeMap<String, DrumVoice>,
+        seed: &mut u32,
+        sample_files: &BTreeMap<String, PathBuf>,
+        tracks: &[(String, String)],
+    ) -> Option<SynthChange> {
+        let mut changed = None;
+        let mut open = self.open;
+
+        let tracks = with_leftover_tracks(tracks, voices.keys());
+        keep_selection(&mut self.selected_track, &tracks);
+
+        egui::Window::new("Drum Synth")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                match self.selected_track.clone() {
+                    Some(selected) => {
+                        track_picker(ui, "synth_track", &tracks, &mut self.selected_track);
+                        ui.add_space(6.0);
+
+                        match sample_files.get(&selected) {
+                            Some(path) => {
+                                ui.weak(format!("Plays the sample file {}", path.display()));
+                                ui.add_space(6.0);
+                                if ui.button("Use Synthesized Sound").clicked() {
+                                    changed = Some(SynthChange::UnloadFile(selected));
+                                }
+                            }
+                            None => {
+                                if Self::voice_section(ui, voices, &selected) {
+                                    changed = Some(SynthChange::Voice(selected.clone()));
+                                }
+                                if ui.button("Load Sample File...").clicked() {
+                                    changed = Some(SynthChange::LoadFile(selected));
+                                }
+                            }
+                        }
+                    }
+                    None => {
+                        ui.weak("Add patterns to the timeline to shape their sounds");
+                    }
+                }
+
+                ui.separator();
+                if Self::seed_section(ui, seed) {
+                    changed = Some(SynthChange::Seed);
+                }
+            });
+
+        self.open = open;
+        changed
+    }
+
+    /// Voice of the track playing `sample_name`. Returns true when it changed.
+    fn voice_section(
+        ui: &mut egui::Ui,
+        voices: &mut BTreeMap<String, DrumVoice>,
+        sample_name: &str,
+    ) -> bool {
+        let builtin = DrumVoice::builtin(sample_name);
+        let mut voice = voices.get(sample_name).copied().or(builtin);
+        let mut changed = false;
+
+        match voice.as_mut() {
+            Some(voice) => {
+                ui.horizontal(|ui| {
+                    ui.label("Model");
+                    egui::ComboBox::from_id_source("synth_model")
+                        .selected_text(voice.model.display_name())
+                        .show_ui(ui, |ui| {
+                            for model in DrumModel::ALL {
+                                // A new model starts from its own built-in sound
+                                if ui
+                                    .selectable_label(voice.model == model, model.display_name())
+                                    .clicked()
+                                    && voice.model != model
+                                {
+                                    *voice = DrumVoice::new(model);
+                                    changed = true;
+                                }
+                            }
+                        });
+                });
+                ui.add_space(6.0);
+                changed |= Self::params_grid(ui, &mut voice.params);
+            }
+            None => {
+                ui.weak("This track has no sound yet");
+                if ui.button("Synthesize").clicked() {
+                    voice = Some(DrumVoice::new(DrumModel::Kick));
+                    changed = true;
+                }
+            }
+        }
+
+        ui.add_space(6.0);
+        let reset_label = if builtin.is_some() {
+            "Reset to Built-in"
+        } else {
+            "Remove Sound"
+        };
+        if ui
+            .add_enabled(
+                voices.contains_key(sample_name),
+                egui::Button::new(reset_label),
+            )
+            .clicked()
+        {
+            voice = builtin;
+            changed = true;
+        }
+
+        if changed {
+            // Built-in sounds left as they are aren't saved
+            match voice {
+                Some(voice) if Some(voice) != builtin => {
+                    voices.insert(sample_name.to_string(), voice);
+                }
+                _ => {
+                    voices.remove(sample_name);
+                }
+            }
+        }
+        changed
+    }
+
+    /// Kit seed. Returns true when it changed.
+    fn seed_section(ui: &mut egui::Ui, seed: &mut u32) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label("Noise Seed");
+            changed |= ui.add(egui::DragValue::new(seed)).changed();
+            if ui.button("Randomize").clicked() {
+                // Any new value will do; the clock is as good a source as any
+                let nanos = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |elapsed| elapsed.subsec_nanos());
+                *seed = seed.wrapping_add(nanos | 1);
+                changed = true;
+            }
+        });
+        ui.weak("The same seed renders the same sounds on every machine");
+        changed
+    }
+
+    fn params_grid(ui: &mut egui::Ui, params: &mut SynthParams) -> bool {
+        let mut changed = false;
+        egui::Grid::new("synth_params_grid")
+            .num_columns(2)
+            .show(ui, |ui| {
+                changed |= frequency_row(ui, "Pitch", &mut params.pitch, 20.0..=11000.0);
+                changed |= slider_row(ui, "Sweep", &mut params.sweep, 0.0..=48.0, " st");
+                ui.label("Decay");
+                changed |= ui
+                    .add(
+                        egui::Slider::new(&mut params.decay, 0.01..=10.0)
+                            .logarithmic(true)
+                            .suffix(" s"),
+                    )
+                    .changed();
+                ui.end_row();
+                changed |= percent_row(ui, "Tone", &mut params.tone);
+                changed |= percent_row(ui, "Noise", &mut params.noise);
+                changed |= percent_row(ui, "Click", &mut params.click);
+                changed |= percent_row(ui, "Level", &mut params.level);
+            });
+        changed
+    }
+}
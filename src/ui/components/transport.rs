@@ -28,6 +28,18 @@ impl TransportControls {
                 timeline.lock().unwrap().stop();
                 state_changed = true;
             }
+
+            let is_looping = timeline.lock().unwrap().is_looping();
+            if ui
+                .selectable_label(is_looping, "🔁 Loop")
+                .on_hover_text(
+                    "Drag on the time ruler to set the loop region, right-click it to clear",
+                )
+                .clicked()
+            {
+                timeline.lock().unwrap().toggle_loop();
+                state_changed = true;
+            }
         });
 
         state_changed